@@ -0,0 +1,60 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+/// Rounds the rating looks back over
+const RECENT_ROUNDS: usize = 5;
+/// A success where a press nearly ran out the turn timer counts for this much
+const CLOSE_CALL_RESULT: f32 = 0.5;
+/// Ratings at or above this speed the game up, at or below `STRUGGLING` slow it down
+const CRUISING: f32 = 0.8;
+const STRUGGLING: f32 = 0.5;
+/// How much the pace changes each round
+const PACE_STEP: f32 = 0.05;
+/// Bounds for the pace, so it never gets too far from the normal timings
+const MIN_PACE: f32 = 0.8;
+const MAX_PACE: f32 = 1.3;
+/// The share of the turn timer left that counts as a close call
+pub const CLOSE_CALL_TIME_LEFT: f32 = 0.25;
+
+/// How the player has been doing lately, for adaptive difficulty
+#[derive(Resource, Default)]
+pub struct Performance {
+    /// Recent rounds, 1 for a clean success down to 0 for a failure
+    recent: VecDeque<f32>,
+    /// Whether a press this round nearly ran out the turn timer
+    pub close_call: bool,
+}
+
+impl Performance {
+    /// Adds the round that just ended to the recent rounds
+    pub fn record(&mut self, succeeded: bool) {
+        let result = match (succeeded, self.close_call) {
+            (false, _) => 0.0,
+            (true, true) => CLOSE_CALL_RESULT,
+            (true, false) => 1.0,
+        };
+        self.recent.push_back(result);
+        if self.recent.len() > RECENT_ROUNDS {
+            self.recent.pop_front();
+        }
+        self.close_call = false;
+    }
+
+    /// The average of the recent rounds, if there are any
+    fn rating(&self) -> Option<f32> {
+        (!self.recent.is_empty())
+            .then(|| self.recent.iter().sum::<f32>() / self.recent.len() as f32)
+    }
+
+    /// Nudges the pace to keep the player challenged, slower when they're
+    /// struggling and faster when they're cruising
+    pub fn adjust(&self, pace: f32) -> f32 {
+        let pace = match self.rating() {
+            Some(rating) if rating >= CRUISING => pace - PACE_STEP,
+            Some(rating) if rating <= STRUGGLING => pace + PACE_STEP,
+            _ => pace,
+        };
+        pace.clamp(MIN_PACE, MAX_PACE)
+    }
+}
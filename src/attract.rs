@@ -0,0 +1,153 @@
+use bevy::{input::mouse::MouseMotion, prelude::*};
+use iyes_loopless::prelude::*;
+
+use crate::{ghost::GhostReplay, AppState, Button, ButtonEvent, ButtonState};
+
+/// Plays a demo game on the main menu when nobody is around
+pub struct AttractPlugin;
+
+impl Plugin for AttractPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AttractMode>()
+            .add_enter_system(AppState::Menu, reset_attract_mode)
+            .add_exit_system(AppState::Menu, stop_attract_mode)
+            .add_system(detect_idle.run_in_state(AppState::Menu))
+            .add_system(play_attract_demo.run_in_state(AppState::Menu));
+    }
+}
+
+/// How long the menu has to be idle before the demo starts
+const ATTRACT_IDLE_SECONDS: f32 = 10.0;
+/// The demo pattern restarts once it's this long
+const ATTRACT_MAX_LENGTH: usize = 6;
+
+/// The idle timer and the demo game, if one is playing
+#[derive(Resource)]
+struct AttractMode {
+    idle: Timer,
+    demo: Option<AttractDemo>,
+}
+
+impl Default for AttractMode {
+    fn default() -> Self {
+        Self {
+            idle: Timer::from_seconds(ATTRACT_IDLE_SECONDS, TimerMode::Once),
+            demo: None,
+        }
+    }
+}
+
+/// A self-playing game of Simon
+struct AttractDemo {
+    pattern: Vec<Button>,
+    step: AttractStep,
+    beat: Timer,
+}
+
+/// What the demo is doing
+#[derive(Clone, Copy)]
+enum AttractStep {
+    /// Showing the pattern, like `MonkeySee`
+    Showing(usize),
+    /// Pretending to be a player, like `MonkeyDo`
+    Repeating(usize),
+}
+
+impl AttractDemo {
+    fn new() -> Self {
+        Self {
+            pattern: vec![rand::random()],
+            step: AttractStep::Showing(0),
+            beat: Timer::from_seconds(1.0, TimerMode::Repeating),
+        }
+    }
+
+    /// Lights or presses the next button, starting a new round when done
+    fn advance(&mut self) -> Option<ButtonEvent> {
+        match self.step {
+            AttractStep::Showing(index) => {
+                if let Some(button) = self.pattern.get(index) {
+                    self.step = AttractStep::Showing(index + 1);
+                    Some(ButtonEvent::Lit(*button))
+                } else {
+                    self.step = AttractStep::Repeating(0);
+                    None
+                }
+            }
+            AttractStep::Repeating(index) => {
+                if let Some(button) = self.pattern.get(index) {
+                    self.step = AttractStep::Repeating(index + 1);
+                    Some(ButtonEvent::Pressed(*button))
+                } else {
+                    if self.pattern.len() >= ATTRACT_MAX_LENGTH {
+                        self.pattern.clear();
+                    }
+                    self.pattern.push(rand::random());
+                    self.step = AttractStep::Showing(0);
+                    None
+                }
+            }
+        }
+    }
+}
+
+/// Starts counting from zero when the menu opens
+fn reset_attract_mode(mut attract_mode: ResMut<AttractMode>) {
+    *attract_mode = AttractMode::default();
+}
+
+/// Stops the demo when leaving the menu
+fn stop_attract_mode(mut attract_mode: ResMut<AttractMode>, mut buttons: Query<&mut ButtonState>) {
+    end_demo(&mut attract_mode, &mut buttons);
+}
+
+/// Stops the demo and turns off any buttons it lit
+fn end_demo(attract_mode: &mut AttractMode, buttons: &mut Query<&mut ButtonState>) {
+    if attract_mode.demo.take().is_some() {
+        for mut state in buttons.iter_mut() {
+            *state = ButtonState::Inactive;
+        }
+    }
+}
+
+/// Resets the idle timer on any input or while the best run is replayed,
+/// starting the demo when it runs out
+#[allow(clippy::too_many_arguments)]
+fn detect_idle(
+    mut attract_mode: ResMut<AttractMode>,
+    ghost_replay: Option<Res<GhostReplay>>,
+    mut buttons: Query<&mut ButtonState>,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    time: Res<Time>,
+) {
+    let input = keys.get_just_pressed().next().is_some()
+        || mouse_buttons.get_just_pressed().next().is_some()
+        || gamepad_buttons.get_just_pressed().next().is_some()
+        || mouse_motion.iter().count() > 0
+        || ghost_replay.is_some();
+
+    if input {
+        attract_mode.idle.reset();
+        end_demo(&mut attract_mode, &mut buttons);
+    } else if attract_mode.idle.tick(time.delta()).just_finished() {
+        attract_mode.demo = Some(AttractDemo::new());
+    }
+}
+
+/// Steps through the demo game
+fn play_attract_demo(
+    mut attract_mode: ResMut<AttractMode>,
+    mut button_event_writer: EventWriter<ButtonEvent>,
+    time: Res<Time>,
+) {
+    if let Some(demo) = &mut attract_mode.demo {
+        if demo.beat.tick(time.delta()).just_finished() {
+            if let Some(event) = demo.advance() {
+                button_event_writer.send(event);
+            }
+        }
+    }
+}
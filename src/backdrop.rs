@@ -0,0 +1,111 @@
+use bevy::{
+    pbr::{NotShadowCaster, NotShadowReceiver},
+    prelude::{shape::Quad, *},
+};
+
+use crate::settings::Settings;
+
+/// A dim grid far behind the desk, slowly drifting upwards
+pub struct BackdropPlugin;
+
+impl Plugin for BackdropPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup_backdrop)
+            .add_system(apply_backdrop)
+            .add_system(drift_backdrop);
+    }
+}
+
+/// How far in front of the camera, behind the desk, the grid sits
+const BACKDROP_DISTANCE: f32 = 8.0;
+/// Gap between grid lines
+const BACKDROP_SPACING: f32 = 1.0;
+const BACKDROP_LINE_WIDTH: f32 = 0.02;
+/// How many lines there are either side of the middle, enough to fill the widest view
+const BACKDROP_COLUMNS: i32 = 16;
+const BACKDROP_ROWS: i32 = 10;
+/// Kept dark so the buttons and the lamp stay the focus
+const BACKDROP_COLOR: Color = Color::rgb(0.06, 0.08, 0.12);
+/// How fast the grid drifts, in lines per second
+const BACKDROP_DRIFT: f32 = 0.05;
+
+/// Marker component for the grid
+#[derive(Component)]
+struct Backdrop;
+
+/// Spawns the grid, hidden until the setting is turned on
+fn setup_backdrop(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // Unlit, so the lamp doesn't light it up
+    let material = materials.add(StandardMaterial {
+        base_color: BACKDROP_COLOR,
+        unlit: true,
+        ..Default::default()
+    });
+    // Each line is long enough to still reach the edges as the grid drifts
+    let width = (BACKDROP_COLUMNS * 2 + 2) as f32 * BACKDROP_SPACING;
+    let height = (BACKDROP_ROWS * 2 + 2) as f32 * BACKDROP_SPACING;
+    let column = meshes.add(Quad::new(Vec2::new(BACKDROP_LINE_WIDTH, height)).into());
+    let row = meshes.add(Quad::new(Vec2::new(width, BACKDROP_LINE_WIDTH)).into());
+
+    commands
+        .spawn(SpatialBundle {
+            transform: Transform::from_xyz(0.0, 0.0, -BACKDROP_DISTANCE),
+            visibility: Visibility::INVISIBLE,
+            ..Default::default()
+        })
+        .insert(Backdrop)
+        .with_children(|parent| {
+            let columns = (-BACKDROP_COLUMNS..=BACKDROP_COLUMNS)
+                .map(|x| (column.clone(), Vec3::X * x as f32 * BACKDROP_SPACING));
+            let rows = (-BACKDROP_ROWS..=BACKDROP_ROWS)
+                .map(|y| (row.clone(), Vec3::Y * y as f32 * BACKDROP_SPACING));
+            for (mesh, translation) in columns.chain(rows) {
+                parent
+                    .spawn(PbrBundle {
+                        mesh,
+                        material: material.clone(),
+                        transform: Transform::from_translation(translation),
+                        ..Default::default()
+                    })
+                    .insert(NotShadowCaster)
+                    .insert(NotShadowReceiver);
+            }
+        });
+}
+
+/// Shows the grid when it's turned on, except in high contrast where it
+/// would only get in the way
+fn apply_backdrop(settings: Res<Settings>, mut backdrops: Query<&mut Visibility, With<Backdrop>>) {
+    if !settings.is_changed() {
+        return;
+    }
+    let visible = settings.backdrop && !settings.high_contrast;
+    for mut visibility in backdrops.iter_mut() {
+        if visibility.is_visible != visible {
+            visibility.is_visible = visible;
+        }
+    }
+}
+
+/// Drifts the grid upwards, wrapping every line so it looks endless
+///
+/// Stays still with reduced motion or in performance mode
+fn drift_backdrop(
+    settings: Res<Settings>,
+    mut backdrops: Query<(&mut Transform, &Visibility), With<Backdrop>>,
+    time: Res<Time>,
+) {
+    if settings.reduced_motion || settings.performance_mode {
+        return;
+    }
+    for (mut transform, visibility) in backdrops.iter_mut() {
+        if visibility.is_visible {
+            let drift = time.elapsed_seconds() * BACKDROP_DRIFT;
+            transform.translation.y = drift.fract() * BACKDROP_SPACING;
+        }
+    }
+}
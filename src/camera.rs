@@ -0,0 +1,191 @@
+use std::f32::consts::{PI, TAU};
+
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{desk_transform, settings::Settings, AppState};
+
+/// Moves the camera around the desk, switches between views and sets the field of view
+pub struct CameraControlPlugin;
+
+impl Plugin for CameraControlPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system_to_stage(StartupStage::PostStartup, start_camera_intro)
+            .add_system(play_camera_intro)
+            .add_system(apply_camera_view)
+            .add_system(orbit_camera.after(apply_camera_view))
+            .add_system(apply_camera_fov);
+    }
+}
+
+/// How long the camera takes to settle on the desk
+const CAMERA_INTRO_SECONDS: f32 = 1.5;
+/// Where the camera starts, relative to where it ends up
+const CAMERA_INTRO_OFFSET: Vec3 = Vec3::new(0.6, 0.8, 1.5);
+/// What the camera looks at while pulled back
+const CAMERA_INTRO_FOCUS: Vec3 = Vec3::new(0.0, -0.6, -2.0);
+
+/// Limits for the field of view setting, in degrees
+const MIN_FOV: f32 = 30.0;
+const MAX_FOV: f32 = 90.0;
+
+/// How high above the buttons the flat view is
+const FLAT_VIEW_HEIGHT: f32 = 1.2;
+
+/// How quickly the orbit eases back to the usual view, per second
+const ORBIT_RETURN_RATE: f32 = 4.0;
+/// Close enough to the usual view to snap onto it, in radians
+const ORBIT_SNAP: f32 = 0.001;
+
+/// Eases the camera from `from` to `to`
+#[derive(Component)]
+struct CameraIntro {
+    timer: Timer,
+    from: Transform,
+    to: Transform,
+}
+
+/// Pulls the camera back so it can swoop in, unless motion is reduced
+fn start_camera_intro(
+    mut commands: Commands,
+    mut cameras: Query<(Entity, &mut Transform), With<Camera3d>>,
+    settings: Res<Settings>,
+) {
+    for (entity, mut transform) in cameras.iter_mut() {
+        let to = camera_view(&settings);
+        if settings.reduced_motion {
+            *transform = to;
+            continue;
+        }
+        let from = Transform::from_translation(to.translation + CAMERA_INTRO_OFFSET)
+            .looking_at(CAMERA_INTRO_FOCUS, Vec3::Y);
+        *transform = from;
+        commands.entity(entity).insert(CameraIntro {
+            timer: Timer::from_seconds(CAMERA_INTRO_SECONDS, TimerMode::Once),
+            from,
+            to,
+        });
+    }
+}
+
+/// Moves the camera along the intro, jumping to the end on any input
+fn play_camera_intro(
+    mut commands: Commands,
+    mut cameras: Query<(Entity, &mut Transform, &mut CameraIntro)>,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    touches: Res<Touches>,
+    time: Res<Time>,
+) {
+    let skip = keys.get_just_pressed().next().is_some()
+        || mouse_buttons.get_just_pressed().next().is_some()
+        || gamepad_buttons.get_just_pressed().next().is_some()
+        || touches.iter_just_pressed().next().is_some();
+
+    for (entity, mut transform, mut intro) in cameras.iter_mut() {
+        if skip || intro.timer.tick(time.delta()).finished() {
+            *transform = intro.to;
+            commands.entity(entity).remove::<CameraIntro>();
+        } else {
+            // Smoothstep, so it starts and stops gently
+            let t = intro.timer.percent();
+            let eased = t * t * (3.0 - 2.0 * t);
+            transform.translation = intro.from.translation.lerp(intro.to.translation, eased);
+            transform.rotation = intro.from.rotation.slerp(intro.to.rotation, eased);
+        }
+    }
+}
+
+/// Where the camera sits for the chosen view
+fn camera_view(settings: &Settings) -> Transform {
+    if settings.flat_view {
+        // Straight down onto the top of the desk, with the far edge at the top
+        let desk = desk_transform(settings);
+        let up = desk.rotation * Vec3::Y;
+        let focus = desk.translation + up * 0.47;
+        Transform::from_translation(focus + up * FLAT_VIEW_HEIGHT)
+            .looking_at(focus, desk.rotation * Vec3::NEG_Z)
+    } else {
+        Transform::IDENTITY
+    }
+}
+
+/// Moves the camera when the view setting changes
+fn apply_camera_view(
+    settings: Res<Settings>,
+    mut cameras: Query<(&mut Transform, Option<&mut CameraIntro>), With<Camera3d>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    let view = camera_view(&settings);
+    for (mut transform, intro) in cameras.iter_mut() {
+        // Let the intro finish where it should
+        if let Some(mut intro) = intro {
+            intro.to = view;
+        } else if *transform != view {
+            *transform = view;
+        }
+    }
+}
+
+/// Slowly circles the desk on the menu and game over screens, if turned on,
+/// and eases back to the usual view everywhere else
+fn orbit_camera(
+    mut cameras: Query<&mut Transform, With<Camera3d>>,
+    intros: Query<(), With<CameraIntro>>,
+    app_state: Res<CurrentState<AppState>>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+    mut angle: Local<f32>,
+) {
+    // Wait for the intro, so the orbit starts from where it settles
+    if !intros.is_empty() {
+        return;
+    }
+    let showing_off = matches!(app_state.0, AppState::Menu | AppState::GameOver);
+    match settings.camera_orbit {
+        Some(speed) if showing_off && !settings.reduced_motion => {
+            let turned = *angle + speed.to_radians() * time.delta_seconds();
+            // Kept between -PI and PI so it eases back the short way round
+            *angle = (turned + PI).rem_euclid(TAU) - PI;
+        }
+        _ if *angle == 0.0 => return,
+        _ => {
+            *angle *= (1.0 - ORBIT_RETURN_RATE * time.delta_seconds()).max(0.0);
+            if angle.abs() < ORBIT_SNAP {
+                *angle = 0.0;
+            }
+        }
+    }
+
+    let mut orbited = camera_view(&settings);
+    orbited.rotate_around(
+        desk_transform(&settings).translation,
+        Quat::from_rotation_y(*angle),
+    );
+    for mut transform in cameras.iter_mut() {
+        *transform = orbited;
+    }
+}
+
+/// Changes the camera's field of view when the setting changes
+///
+/// Picking builds its rays from the projection every frame, so it stays accurate
+fn apply_camera_fov(
+    settings: Res<Settings>,
+    mut projections: Query<&mut Projection, With<Camera3d>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    let fov = settings.fov.clamp(MIN_FOV, MAX_FOV).to_radians();
+    for mut projection in projections.iter_mut() {
+        if let Projection::Perspective(perspective) = &mut *projection {
+            if perspective.fov != fov {
+                perspective.fov = fov;
+            }
+        }
+    }
+}
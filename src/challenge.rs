@@ -0,0 +1,85 @@
+use std::fs;
+
+use bevy::{ecs::event::ManualEventReader, prelude::*};
+use iyes_loopless::prelude::*;
+
+use crate::{
+    generator::{ListGenerator, PatternGenerator},
+    menu::MenuAction,
+    toast::Toast,
+    AppState, Button,
+};
+
+/// Plays a pattern written by the player instead of a random one
+pub struct ChallengePlugin;
+
+impl Plugin for ChallengePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_enter_system(AppState::Menu, end_challenge)
+            .add_system(start_challenge.run_in_state(AppState::Menu));
+    }
+}
+
+/// Where the challenge is read from, a RON list of colors like `[Red, Green, Blue]`
+const CHALLENGE_PATH: &str = "challenge.ron";
+/// Load errors stay up longer than most toasts so they can be read
+const CHALLENGE_ERROR_SECONDS: f32 = 6.0;
+
+/// The pattern being played, given to `update_pattern` by a `ListGenerator`
+///
+/// Reaching the end of it wins the run
+#[derive(Resource)]
+pub struct Challenge(pub Vec<Button>);
+
+/// Reads the challenge file, saying what's wrong with it if it can't be played
+fn load_challenge(path: &str) -> Result<Vec<Button>, String> {
+    let contents =
+        fs::read_to_string(path).map_err(|error| format!("Couldn't read {path}: {error}"))?;
+    let buttons: Vec<Button> = ron::from_str(&contents).map_err(|error| {
+        format!("{path} should be a list of Red, Green, Blue and Yellow: {error}")
+    })?;
+    if buttons.is_empty() {
+        return Err(format!("{path} doesn't have any buttons in it"));
+    }
+    Ok(buttons)
+}
+
+/// Loads the challenge and starts a run with it, or shows why it couldn't be loaded
+///
+/// Starts the run with `MenuAction::Play`, so it reads the events by hand to
+/// be able to send one
+fn start_challenge(
+    mut commands: Commands,
+    mut reader: Local<ManualEventReader<MenuAction>>,
+    mut menu_events: ResMut<Events<MenuAction>>,
+    mut toast_writer: EventWriter<Toast>,
+) {
+    if !reader
+        .iter(&menu_events)
+        .any(|event| *event == MenuAction::Challenge)
+    {
+        return;
+    }
+    match load_challenge(CHALLENGE_PATH) {
+        Ok(buttons) => {
+            commands.insert_resource(PatternGenerator(Box::new(ListGenerator(buttons.clone()))));
+            commands.insert_resource(Challenge(buttons));
+            menu_events.send(MenuAction::Play);
+        }
+        Err(error) => {
+            warn!("{error}");
+            toast_writer.send(Toast {
+                text: error,
+                seconds: CHALLENGE_ERROR_SECONDS,
+            });
+        }
+    }
+}
+
+/// Goes back to the usual patterns when the challenge is left
+fn end_challenge(mut commands: Commands, challenge: Option<Res<Challenge>>) {
+    if challenge.is_some() {
+        commands.remove_resource::<Challenge>();
+        commands.insert_resource(PatternGenerator::from_args());
+    }
+}
@@ -0,0 +1,282 @@
+use std::f32::consts::TAU;
+
+use bevy::{
+    audio::{play_queued_audio_system, AudioOutput, Decodable, Source},
+    prelude::*,
+    reflect::TypeUuid,
+};
+use iyes_loopless::prelude::*;
+
+use crate::{
+    effects::Lifetime,
+    fonts::{ThemedText, UiFont},
+    settings::Settings,
+    AppState, GameMode, SimonEvent,
+};
+
+/// Counts rounds cleared in a row, and makes a show of losing the streak
+pub struct ComboPlugin;
+
+impl Plugin for ComboPlugin {
+    fn build(&self, app: &mut App) {
+        // The shatter is generated rather than loaded, so it gets its own audio output
+        app.init_non_send_resource::<AudioOutput<ShatterSound>>()
+            .add_asset::<ShatterSound>()
+            .init_resource::<Audio<ShatterSound>>()
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                play_queued_audio_system::<ShatterSound>,
+            )
+            .init_resource::<Streak>()
+            .add_event::<StreakBroken>()
+            .add_startup_system(setup_streak)
+            .add_enter_system(AppState::Menu, reset_streak)
+            .add_enter_system(AppState::Victory, reset_streak)
+            .add_system(update_streak)
+            .add_system(update_streak_label)
+            .add_system(break_streak)
+            .add_system(crumble_broken_streaks);
+    }
+}
+
+/// How long the broken streak takes to fall apart
+const BREAK_SECONDS: f32 = 0.8;
+/// How far each half of the broken streak drifts sideways, in pixels
+const BREAK_SPREAD: f32 = 40.0;
+/// How far the broken streak falls, in pixels
+const BREAK_FALL: f32 = 60.0;
+const BROKEN_STREAK_COLOR: Color = Color::rgb(1.0, 0.3, 0.3);
+const SHATTER_VOLUME: f32 = 0.6;
+const SHATTER_SAMPLE_RATE: u32 = 44_100;
+const SHATTER_SECONDS: f32 = 0.5;
+/// High, quickly fading tones for the glassy part of the shatter
+const SHATTER_CHIMES: [(f32, f32); 4] =
+    [(2637.0, 9.0), (3136.0, 12.0), (3951.0, 7.0), (4699.0, 15.0)];
+
+/// Rounds cleared in a row this run without a mistake
+#[derive(Resource, Default)]
+struct Streak(usize);
+
+/// Event for a mistake ending a streak of more than one round
+struct StreakBroken(usize);
+
+/// Marker component for the streak label
+#[derive(Component)]
+struct StreakLabel;
+
+/// One half of a broken streak, drifting left or right as it falls
+#[derive(Component)]
+struct StreakShard {
+    direction: f32,
+}
+
+/// A breaking glass sound
+#[derive(TypeUuid)]
+#[uuid = "8e2d41c7-3f6a-4b8e-9d15-2a7c6e0b94f3"]
+struct ShatterSound;
+
+/// Generates the `ShatterSound`
+struct ShatterDecoder {
+    sample: u32,
+    noise: u32,
+}
+
+impl Iterator for ShatterDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let t = self.sample as f32 / SHATTER_SAMPLE_RATE as f32;
+        if t >= SHATTER_SECONDS {
+            return None;
+        }
+        self.sample += 1;
+        // A cheap xorshift is plenty for a burst of noise
+        self.noise ^= self.noise << 13;
+        self.noise ^= self.noise >> 17;
+        self.noise ^= self.noise << 5;
+        let noise = self.noise as f32 / u32::MAX as f32 * 2.0 - 1.0;
+        let crash = noise * (-t * 14.0).exp();
+        let chimes: f32 = SHATTER_CHIMES
+            .iter()
+            .map(|(frequency, decay)| (t * frequency * TAU).sin() * (-t * decay).exp())
+            .sum();
+        Some((crash * 0.6 + chimes * 0.1) * SHATTER_VOLUME)
+    }
+}
+
+impl Source for ShatterDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SHATTER_SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<std::time::Duration> {
+        Some(std::time::Duration::from_secs_f32(SHATTER_SECONDS))
+    }
+}
+
+impl Decodable for ShatterSound {
+    type Decoder = ShatterDecoder;
+    type DecoderItem = f32;
+
+    fn decoder(&self) -> Self::Decoder {
+        ShatterDecoder {
+            sample: 0,
+            noise: 0x9e37_79b9,
+        }
+    }
+}
+
+/// The shatter sound
+#[derive(Resource)]
+struct Shatter(Handle<ShatterSound>);
+
+/// Adds the shatter sound and the streak label
+fn setup_streak(
+    mut commands: Commands,
+    mut sounds: ResMut<Assets<ShatterSound>>,
+    font: Res<UiFont>,
+    settings: Res<Settings>,
+) {
+    commands.insert_resource(Shatter(sounds.add(ShatterSound)));
+    commands
+        .spawn(TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: font.handle.clone(),
+                    font_size: 36.0,
+                    color: settings.ui_text_color.color(),
+                },
+            ),
+            style: streak_style(),
+            visibility: Visibility::INVISIBLE,
+            ..Default::default()
+        })
+        .insert((StreakLabel, ThemedText));
+}
+
+/// Where the streak label sits
+fn streak_style() -> Style {
+    Style {
+        position_type: PositionType::Absolute,
+        position: UiRect {
+            top: Val::Percent(2.0),
+            left: Val::Percent(2.0),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Starts each run without a streak
+fn reset_streak(mut streak: ResMut<Streak>) {
+    streak.0 = 0;
+}
+
+/// Counts cleared rounds, and ends the streak on a mistake
+fn update_streak(
+    mut event_reader: EventReader<SimonEvent>,
+    mut broken_writer: EventWriter<StreakBroken>,
+    mut streak: ResMut<Streak>,
+) {
+    for event in event_reader.iter() {
+        match event {
+            SimonEvent::Success => streak.0 += 1,
+            SimonEvent::Failure => {
+                if streak.0 > 1 {
+                    broken_writer.send(StreakBroken(streak.0));
+                }
+                streak.0 = 0;
+            }
+            SimonEvent::Next => {}
+        }
+    }
+}
+
+/// Shows the streak once there's more than one round in it, except in zen mode
+fn update_streak_label(
+    streak: Res<Streak>,
+    game_mode: Res<GameMode>,
+    mut labels: Query<(&mut Text, &mut Visibility), With<StreakLabel>>,
+) {
+    if !streak.is_changed() && !game_mode.is_changed() {
+        return;
+    }
+    for (mut text, mut visibility) in labels.iter_mut() {
+        visibility.is_visible = streak.0 > 1 && *game_mode != GameMode::Zen;
+        text.sections[0].value = format!("Streak x{}", streak.0);
+    }
+}
+
+/// Plays the shatter and splits the lost streak in two where the label was
+fn break_streak(
+    mut commands: Commands,
+    mut event_reader: EventReader<StreakBroken>,
+    shatter: Res<Shatter>,
+    audio: Res<Audio<ShatterSound>>,
+    game_mode: Res<GameMode>,
+    font: Res<UiFont>,
+) {
+    for StreakBroken(streak) in event_reader.iter() {
+        if *game_mode == GameMode::Zen {
+            continue;
+        }
+        audio.play(shatter.0.clone());
+        let style = TextStyle {
+            font: font.handle.clone(),
+            font_size: 36.0,
+            color: BROKEN_STREAK_COLOR,
+        };
+        commands
+            .spawn(NodeBundle {
+                style: streak_style(),
+                background_color: Color::NONE.into(),
+                ..Default::default()
+            })
+            .insert(Lifetime(Timer::from_seconds(
+                BREAK_SECONDS,
+                TimerMode::Once,
+            )))
+            .with_children(|parent| {
+                for (half, direction) in
+                    [("Streak ".to_string(), -1.0), (format!("x{streak}"), 1.0)]
+                {
+                    parent
+                        .spawn(TextBundle::from_section(half, style.clone()))
+                        .insert(StreakShard { direction });
+                }
+            });
+    }
+}
+
+/// Pulls the halves of broken streaks apart as they fall and fade,
+/// or just fades them if motion is reduced
+fn crumble_broken_streaks(
+    broken: Query<(&Lifetime, &Children)>,
+    mut shards: Query<(&StreakShard, &mut Style, &mut Text)>,
+    settings: Res<Settings>,
+) {
+    for (lifetime, children) in broken.iter() {
+        let progress = lifetime.0.percent();
+        for child in children.iter() {
+            let Ok((shard, mut style, mut text)) = shards.get_mut(*child) else {
+                continue;
+            };
+            if !settings.reduced_motion {
+                style.position.left = Val::Px(shard.direction * BREAK_SPREAD * progress);
+                style.position.top = Val::Px(BREAK_FALL * progress * progress);
+            }
+            for section in text.sections.iter_mut() {
+                section.style.color.set_a(1.0 - progress);
+            }
+        }
+    }
+}
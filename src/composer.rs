@@ -0,0 +1,194 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{
+    fonts::UiFont,
+    ghost::GhostPress,
+    menu::{menu_text_style, spawn_menu_button, MenuAction},
+    press_buttons,
+    settings::Settings,
+    AppState, ButtonEvent,
+};
+
+/// A toy mode where the player makes up a pattern and the game plays it back
+pub struct ComposerPlugin;
+
+impl Plugin for ComposerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_enter_system(AppState::Composer, start_composer)
+            .add_exit_system(AppState::Composer, end_composer)
+            .add_system(
+                press_buttons
+                    .run_in_state(AppState::Composer)
+                    .run_if(is_recording),
+            )
+            .add_system(record_composition.run_in_state(AppState::Composer))
+            .add_system(play_composition.run_in_state(AppState::Composer))
+            .add_system(update_composer_status.run_in_state(AppState::Composer));
+    }
+}
+
+/// How long after the last press the recording stops
+const COMPOSER_IDLE_SECONDS: f32 = 2.0;
+/// The longest a recording can go on for
+const COMPOSER_MAX_SECONDS: f32 = 10.0;
+/// Time after playback before the next recording can start
+const COMPOSER_REST_SECONDS: f32 = 1.0;
+
+/// The pattern being made up or played back
+#[derive(Resource, Default)]
+struct Composition {
+    presses: Vec<GhostPress>,
+    /// Seconds since the first press, or since playback started
+    elapsed: f32,
+    phase: ComposerPhase,
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+enum ComposerPhase {
+    /// Waiting for the player to press something
+    #[default]
+    Waiting,
+    /// Recording the player's presses
+    Recording,
+    /// Playing the presses back
+    Playback,
+}
+
+/// Marker component for the composer's screen
+#[derive(Component)]
+struct ComposerScreen;
+
+/// Marker component for the text saying what the composer is doing
+#[derive(Component)]
+struct ComposerStatus;
+
+/// Whether presses are being taken
+fn is_recording(composition: Option<Res<Composition>>) -> bool {
+    composition.is_some_and(|composition| composition.phase != ComposerPhase::Playback)
+}
+
+/// Starts with an empty composition and shows what to do
+fn start_composer(mut commands: Commands, font: Res<UiFont>, settings: Res<Settings>) {
+    commands.init_resource::<Composition>();
+    let title_style = menu_text_style(&font, 64.0, settings.ui_text_color.color());
+    let label_style = menu_text_style(&font, 28.0, settings.ui_text_color.color());
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Percent(4.0),
+                    ..Default::default()
+                },
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            background_color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .insert(ComposerScreen)
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("Composer", title_style));
+            parent
+                .spawn(TextBundle::from_section("", label_style.clone()))
+                .insert(ComposerStatus);
+            spawn_menu_button(parent, MenuAction::MainMenu, "Main Menu", &label_style);
+        });
+}
+
+/// Records presses until the player stops, then starts the playback
+fn record_composition(
+    mut composition: ResMut<Composition>,
+    mut button_event_reader: EventReader<ButtonEvent>,
+    time: Res<Time>,
+) {
+    if composition.phase == ComposerPhase::Playback {
+        button_event_reader.clear();
+        return;
+    }
+    if composition.phase == ComposerPhase::Recording {
+        composition.elapsed += time.delta_seconds();
+    }
+
+    for event in button_event_reader.iter() {
+        if let ButtonEvent::Pressed(button) = event {
+            if composition.phase == ComposerPhase::Waiting {
+                composition.phase = ComposerPhase::Recording;
+                composition.elapsed = 0.0;
+            }
+            let seconds = composition.elapsed;
+            composition.presses.push(GhostPress {
+                button: *button,
+                seconds,
+            });
+        }
+    }
+
+    let Some(last) = composition.presses.last() else {
+        return;
+    };
+    if composition.elapsed - last.seconds >= COMPOSER_IDLE_SECONDS
+        || composition.elapsed >= COMPOSER_MAX_SECONDS
+    {
+        composition.phase = ComposerPhase::Playback;
+        composition.elapsed = -COMPOSER_REST_SECONDS;
+    }
+}
+
+/// Lights the recorded buttons at the times they were pressed, then waits
+/// for a new recording
+fn play_composition(
+    mut composition: ResMut<Composition>,
+    mut button_event_writer: EventWriter<ButtonEvent>,
+    time: Res<Time>,
+) {
+    if composition.phase != ComposerPhase::Playback {
+        return;
+    }
+    // Playback starts below zero, so the first press at zero isn't skipped
+    let previous = composition.elapsed;
+    composition.elapsed += time.delta_seconds();
+    let elapsed = composition.elapsed;
+
+    for press in composition.presses.iter() {
+        if press.seconds > previous && press.seconds <= elapsed {
+            button_event_writer.send(ButtonEvent::Lit(press.button));
+        }
+    }
+    let end = composition.presses.last().map_or(0.0, |last| last.seconds);
+    if elapsed >= end + COMPOSER_REST_SECONDS {
+        composition.presses.clear();
+        composition.phase = ComposerPhase::Waiting;
+    }
+}
+
+/// Says what the composer is waiting for
+fn update_composer_status(
+    composition: Res<Composition>,
+    mut statuses: Query<&mut Text, With<ComposerStatus>>,
+) {
+    if !composition.is_changed() {
+        return;
+    }
+    let status = match composition.phase {
+        ComposerPhase::Waiting => "Play a pattern on the buttons",
+        ComposerPhase::Recording => "Recording, stop to hear it back",
+        ComposerPhase::Playback => "Playing it back",
+    };
+    for mut text in statuses.iter_mut() {
+        if text.sections[0].value != status {
+            text.sections[0].value = status.into();
+        }
+    }
+}
+
+/// Throws away the composition when leaving
+fn end_composer(mut commands: Commands, screens: Query<Entity, With<ComposerScreen>>) {
+    commands.remove_resource::<Composition>();
+    for entity in screens.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,214 @@
+use std::env;
+
+use bevy::{
+    asset::LoadState,
+    diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+};
+use iyes_loopless::prelude::*;
+
+use crate::{
+    button_sound_path, fonts::UI_FONT_PATH, settings::Settings, AppState, Button, ButtonEvent,
+    SimonEvent, SimonState,
+};
+
+/// Development helpers, turned on with command line flags
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        if env::args().any(|arg| arg == LOG_EVENTS_FLAG) {
+            app.add_system_to_stage(CoreStage::Last, log_events);
+        }
+        if env::args().any(|arg| arg == FRAME_BUDGET_FLAG) {
+            app.add_plugin(FrameTimeDiagnosticsPlugin)
+                .add_system_to_stage(CoreStage::Last, log_slow_frames);
+        }
+        if env::args().any(|arg| arg == SELF_TEST_FLAG) {
+            app.add_startup_system(start_self_test)
+                .add_system(run_self_test.run_if_resource_exists::<SelfTest>());
+        }
+    }
+}
+
+/// Logs every game event, button event and state change
+const LOG_EVENTS_FLAG: &str = "--log-events";
+
+/// Warns about frames that take longer than `FRAME_BUDGET_MS`
+const FRAME_BUDGET_FLAG: &str = "--frame-budget";
+/// About 30 frames per second
+const FRAME_BUDGET_MS: f64 = 33.0;
+
+/// Checks the assets, buttons and input devices on launch
+const SELF_TEST_FLAG: &str = "--self-test";
+/// Time between lighting each button in the self test
+const SELF_TEST_BEAT_SECONDS: f32 = 0.5;
+
+/// What's left of the self test
+#[derive(Resource)]
+struct SelfTest {
+    assets: Vec<(String, HandleUntyped)>,
+    assets_reported: bool,
+    /// The last button lit, to check on the next beat
+    lit: Option<Button>,
+    buttons: Vec<Button>,
+    beat: Timer,
+}
+
+/// Logs game flow with timestamps, to trace what happened when
+fn log_events(
+    mut simon_events: EventReader<SimonEvent>,
+    mut button_events: EventReader<ButtonEvent>,
+    app_state: Res<CurrentState<AppState>>,
+    state: Res<CurrentState<SimonState>>,
+    time: Res<Time>,
+) {
+    let timestamp = time.elapsed_seconds();
+    if app_state.is_changed() || state.is_changed() {
+        info!("[{timestamp:.3}] state: {:?}/{:?}", app_state.0, state.0);
+    }
+    for event in button_events.iter() {
+        info!("[{timestamp:.3}] {event:?} in {:?}", state.0);
+    }
+    for event in simon_events.iter() {
+        info!("[{timestamp:.3}] {event:?} in {:?}", state.0);
+    }
+}
+
+/// Warns when a frame goes over budget, with the state it happened in
+fn log_slow_frames(
+    diagnostics: Res<Diagnostics>,
+    app_state: Res<CurrentState<AppState>>,
+    state: Res<CurrentState<SimonState>>,
+    time: Res<Time>,
+) {
+    let Some(frame_time) = diagnostics
+        .get(FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .and_then(|diagnostic| diagnostic.value())
+    else {
+        return;
+    };
+    if frame_time > FRAME_BUDGET_MS {
+        warn!(
+            "[{:.3}] slow frame: {frame_time:.1}ms in {:?}/{:?}",
+            time.elapsed_seconds(),
+            app_state.0,
+            state.0
+        );
+    }
+}
+
+/// Starts loading everything the self test checks
+fn start_self_test(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
+) {
+    info!("Self test: starting");
+    let mut paths: Vec<String> = Button::ALL
+        .into_iter()
+        .map(|button| button_sound_path(settings.sound_pack, button))
+        .collect();
+    paths.extend(
+        [
+            "sounds/incorrect.ogg",
+            UI_FONT_PATH,
+            "textures/wood.png",
+            "textures/metal.png",
+        ]
+        .map(String::from),
+    );
+    commands.insert_resource(SelfTest {
+        assets: paths
+            .into_iter()
+            .map(|path| {
+                let handle = asset_server.load_untyped(path.as_str());
+                (path, handle)
+            })
+            .collect(),
+        assets_reported: false,
+        lit: None,
+        // Popped from the back, so reversed to light them in order
+        buttons: Button::ALL.into_iter().rev().collect(),
+        beat: Timer::from_seconds(SELF_TEST_BEAT_SECONDS, TimerMode::Repeating),
+    });
+}
+
+/// Reports on the assets once they've loaded, lights each button in turn, then
+/// lists the input devices and hands over to the menu
+#[allow(clippy::too_many_arguments)]
+fn run_self_test(
+    mut commands: Commands,
+    mut self_test: ResMut<SelfTest>,
+    mut button_event_writer: EventWriter<ButtonEvent>,
+    asset_server: Res<AssetServer>,
+    buttons: Query<(&Button, &Handle<StandardMaterial>)>,
+    materials: Res<Assets<StandardMaterial>>,
+    gamepads: Res<Gamepads>,
+    windows: Res<Windows>,
+    time: Res<Time>,
+) {
+    let self_test = &mut *self_test;
+    if !self_test.assets_reported {
+        let states: Vec<_> = self_test
+            .assets
+            .iter()
+            .map(|(path, handle)| (path, asset_server.get_load_state(handle)))
+            .collect();
+        if states
+            .iter()
+            .all(|(_, state)| matches!(state, LoadState::Loaded | LoadState::Failed))
+        {
+            for (path, state) in states {
+                match state {
+                    LoadState::Loaded => info!("Self test: {path} loaded"),
+                    _ => error!("Self test: {path} FAILED to load"),
+                }
+            }
+            self_test.assets_reported = true;
+        }
+    }
+
+    if !self_test.beat.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    // The last button should still be glowing
+    if let Some(lit) = self_test.lit.take() {
+        let glowing = buttons
+            .iter()
+            .filter(|(button, _)| **button == lit)
+            .filter_map(|(_, handle)| materials.get(handle))
+            .any(|material| material.emissive != Color::BLACK);
+        if glowing {
+            info!("Self test: {lit:?} button lit up");
+        } else {
+            error!("Self test: {lit:?} button didn't light up");
+        }
+    }
+
+    if let Some(button) = self_test.buttons.pop() {
+        button_event_writer.send(ButtonEvent::Lit(button));
+        self_test.lit = Some(button);
+    } else if self_test.assets_reported {
+        let cursor = windows
+            .get_primary()
+            .and_then(|window| window.cursor_position());
+        match cursor {
+            Some(position) => info!("Self test: mouse cursor at {position} in the window"),
+            None => info!("Self test: no mouse cursor in the window"),
+        }
+        info!("Self test: keyboard input is read through the window");
+        let mut count = 0;
+        for gamepad in gamepads.iter() {
+            count += 1;
+            let name = gamepads.name(gamepad).unwrap_or("unknown");
+            info!("Self test: gamepad {} connected ({name})", gamepad.id);
+        }
+        if count == 0 {
+            info!("Self test: no gamepads connected");
+        }
+        info!("Self test: done");
+        commands.remove_resource::<SelfTest>();
+    }
+}
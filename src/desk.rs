@@ -0,0 +1,112 @@
+use bevy::{asset::LoadState, prelude::*};
+
+use crate::{
+    desk_transform,
+    settings::{DeskTheme, Settings},
+    Desk,
+};
+
+/// Textures and colors for the desk
+pub struct DeskPlugin;
+
+impl Plugin for DeskPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(load_desk_textures)
+            .add_system(apply_desk_theme)
+            .add_system(apply_desk_tilt);
+    }
+}
+
+/// The plain desk color
+pub const DESK_COLOR: Color = Color::ANTIQUE_WHITE;
+/// The desk color in high contrast, so the buttons stand out
+const HIGH_CONTRAST_DESK: Color = Color::rgb(0.05, 0.05, 0.05);
+
+/// Textures for the desk themes
+#[derive(Resource)]
+struct DeskTextures {
+    wood: Handle<Image>,
+    metal: Handle<Image>,
+}
+
+impl DeskTheme {
+    /// The theme's texture and the color to use if it fails to load
+    fn texture(self, textures: &DeskTextures) -> Option<(&Handle<Image>, Color)> {
+        match self {
+            DeskTheme::Plain => None,
+            DeskTheme::Wood => Some((&textures.wood, Color::rgb(0.45, 0.28, 0.14))),
+            DeskTheme::Metal => Some((&textures.metal, Color::rgb(0.6, 0.6, 0.62))),
+        }
+    }
+}
+
+/// Starts loading the desk textures
+fn load_desk_textures(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(DeskTextures {
+        wood: asset_server.load("textures/wood.png"),
+        metal: asset_server.load("textures/metal.png"),
+    });
+}
+
+/// Keeps the desk material in line with the theme, using a solid color if its
+/// texture fails to load
+fn apply_desk_theme(
+    settings: Res<Settings>,
+    textures: Res<DeskTextures>,
+    asset_server: Res<AssetServer>,
+    desks: Query<&Handle<StandardMaterial>, With<Desk>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut warned: Local<Vec<DeskTheme>>,
+) {
+    let (color, texture) = if settings.high_contrast {
+        (HIGH_CONTRAST_DESK, None)
+    } else {
+        match settings.desk_theme.texture(&textures) {
+            None => (DESK_COLOR, None),
+            Some((texture, fallback)) => {
+                if asset_server.get_load_state(texture) == LoadState::Failed {
+                    if !warned.contains(&settings.desk_theme) {
+                        warn!(
+                            "Couldn't load the {:?} desk texture, using a solid color",
+                            settings.desk_theme
+                        );
+                        warned.push(settings.desk_theme);
+                    }
+                    (fallback, None)
+                } else {
+                    (Color::WHITE, Some(texture.clone()))
+                }
+            }
+        }
+    };
+
+    for material_handle in desks.iter() {
+        // Only touch the material when something changed, so it isn't re-uploaded every frame
+        let Some(material) = materials.get(material_handle) else {
+            continue;
+        };
+        if material.base_color == color && material.base_color_texture == texture {
+            continue;
+        }
+        if let Some(material) = materials.get_mut(material_handle) {
+            material.base_color = color;
+            material.base_color_texture = texture.clone();
+        }
+    }
+}
+
+/// Tilts the desk when the setting changes
+///
+/// The lamp and buttons are children of the desk, so they tilt with it, and
+/// picking uses their global transforms, so it follows too
+fn apply_desk_tilt(settings: Res<Settings>, mut desks: Query<&mut Transform, With<Desk>>) {
+    if !settings.is_changed() {
+        return;
+    }
+    let rotation = desk_transform(&settings).rotation;
+    for mut transform in desks.iter_mut() {
+        if transform.rotation != rotation {
+            transform.rotation = rotation;
+        }
+    }
+}
@@ -0,0 +1,627 @@
+use std::f32::consts::TAU;
+
+use bevy::{pbr::NotShadowCaster, prelude::*, utils::HashMap};
+use rand::Rng;
+
+use iyes_loopless::prelude::*;
+
+use crate::{
+    button_controller, fonts::UiFont, settings::Settings, toast::Toast, AppState, AudioHandles,
+    Button, ButtonEvent, ButtonState, GameMode, Lamp, NewHighScore, Pattern, PointsAwarded,
+    Scoreboard, SimonEvent, SimonState, WrongPress,
+};
+
+/// Particles, lamp flashes and other celebrations
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Milestone>()
+            .add_event::<ParticleBurst>()
+            .init_resource::<Milestones>()
+            .add_startup_system(setup_particles)
+            .add_system(detect_milestones)
+            .add_system(celebrate_milestones)
+            .add_system(celebrate_high_score)
+            .add_system(glow_scoreboard)
+            .add_system(spawn_particle_bursts)
+            .add_system(update_particles)
+            .add_system(flash_lamp)
+            .add_system(spawn_score_popups)
+            .add_system(float_text)
+            .add_system(despawn_expired)
+            .add_system(slow_motion)
+            .add_system(flash_on_success)
+            .add_system(
+                pulse_on_beat
+                    .run_in_state(AppState::Playing)
+                    .run_in_state(SimonState::MonkeySee),
+            )
+            .add_enter_system(SimonState::MonkeyDo, steady_lamp)
+            .add_system(start_shakes)
+            .add_system(shake_buttons.after(button_controller));
+    }
+}
+
+/// Event for reaching a milestone pattern length
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Milestone(pub usize);
+
+/// Tracks milestone pattern lengths
+#[derive(Resource)]
+pub struct Milestones {
+    /// Celebrate every time the pattern length is a multiple of this
+    pub interval: usize,
+    /// The last milestone celebrated this run
+    pub reached: usize,
+}
+
+impl Default for Milestones {
+    fn default() -> Self {
+        Self {
+            interval: 5,
+            reached: 0,
+        }
+    }
+}
+
+/// Event for spawning particles out of every button
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParticleBurst {
+    pub count: usize,
+    pub speed: f32,
+}
+
+/// Despawns the entity when the timer finishes
+#[derive(Component)]
+pub struct Lifetime(pub Timer);
+
+/// A short-lived bit of confetti
+#[derive(Component)]
+struct Particle {
+    velocity: Vec3,
+}
+
+/// UI text that rises and fades over its `Lifetime`
+#[derive(Component)]
+struct FloatingText {
+    speed: f32,
+}
+
+/// Shared mesh for all particles
+#[derive(Resource)]
+struct ParticleMesh(Handle<Mesh>);
+
+/// Temporarily brightens the lamp
+#[derive(Component)]
+pub struct LampFlash {
+    timer: Timer,
+    base_intensity: f32,
+    boost: f32,
+}
+
+/// Marks a `LampFlash` as a demo beat, which is cut short when the player's
+/// turn starts
+#[derive(Component)]
+struct BeatDip;
+
+/// Fades the scoreboard from gold back to the text color
+#[derive(Component)]
+struct ScoreboardGlow(Timer);
+
+/// Wobbles a wrongly pressed button and tints it red
+#[derive(Component)]
+struct Shake {
+    timer: Timer,
+    origin_x: f32,
+}
+
+const PARTICLE_GRAVITY: f32 = -4.0;
+/// How many times fewer particles are spawned in performance mode
+const PERFORMANCE_PARTICLE_DIVISOR: usize = 4;
+
+/// How long a wrong button shakes for
+const SHAKE_SECONDS: f32 = 0.5;
+/// How far a wrong button shakes, in desk units
+const SHAKE_DISTANCE: f32 = 0.015;
+/// How many times a wrong button shakes back and forth per second
+const SHAKE_FREQUENCY: f32 = 12.0;
+
+/// How fast time runs during the slow-mo after completing a pattern
+const SLOW_MOTION_SPEED: f32 = 0.3;
+/// How long the slow-mo lasts, in real time
+const SLOW_MOTION_SECONDS: f32 = 0.3;
+
+/// How long the scoreboard glows for after a new high score
+const HIGH_SCORE_GLOW_SECONDS: f32 = 2.0;
+const HIGH_SCORE_GOLD: Color = Color::rgb(1.0, 0.8, 0.2);
+
+/// Creates the particle mesh
+fn setup_particles(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    let mesh = meshes.add(shape::Cube { size: 0.02 }.into());
+    commands.insert_resource(ParticleMesh(mesh));
+}
+
+/// Fires a `Milestone` the first time each milestone length is completed
+fn detect_milestones(
+    mut event_reader: EventReader<SimonEvent>,
+    mut milestone_writer: EventWriter<Milestone>,
+    mut milestones: ResMut<Milestones>,
+    pattern: Res<Pattern>,
+) {
+    for event in event_reader.iter() {
+        let length = pattern.0.len();
+        if *event == SimonEvent::Success
+            && length.is_multiple_of(milestones.interval)
+            && length > milestones.reached
+        {
+            milestones.reached = length;
+            milestone_writer.send(Milestone(length));
+        }
+    }
+}
+
+/// Bursts particles, flashes the lamp, plays a chord and pops up a toast on milestones
+#[allow(clippy::too_many_arguments)]
+fn celebrate_milestones(
+    mut commands: Commands,
+    mut event_reader: EventReader<Milestone>,
+    mut burst_writer: EventWriter<ParticleBurst>,
+    mut toast_writer: EventWriter<Toast>,
+    mut lamps: Query<(Entity, &SpotLight, Option<&mut LampFlash>), With<Lamp>>,
+    audio: Res<Audio>,
+    audio_handles: Res<AudioHandles>,
+) {
+    for Milestone(length) in event_reader.iter() {
+        toast_writer.send(Toast::new(format!("Reached length {length}!")));
+        burst_writer.send(ParticleBurst {
+            count: 40,
+            speed: 1.5,
+        });
+
+        for (entity, spot_light, flash) in lamps.iter_mut() {
+            if let Some(mut flash) = flash {
+                flash.timer = Timer::from_seconds(1.0, TimerMode::Once);
+                flash.boost = 4.0;
+            } else {
+                commands.entity(entity).insert(LampFlash {
+                    timer: Timer::from_seconds(1.0, TimerMode::Once),
+                    base_intensity: spot_light.intensity,
+                    boost: 4.0,
+                });
+            }
+        }
+
+        // All four tones an octave up
+        for audio_handle in [
+            &audio_handles.red,
+            &audio_handles.green,
+            &audio_handles.blue,
+            &audio_handles.yellow,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            audio.play_with_settings(
+                audio_handle.clone(),
+                PlaybackSettings::ONCE.with_speed(2.0).with_volume(0.5),
+            );
+        }
+    }
+}
+
+/// Bursts particles, plays a fanfare, glows the scoreboard and pops up a toast
+/// on passing the old high score
+fn celebrate_high_score(
+    mut commands: Commands,
+    mut event_reader: EventReader<NewHighScore>,
+    mut burst_writer: EventWriter<ParticleBurst>,
+    mut toast_writer: EventWriter<Toast>,
+    scoreboards: Query<Entity, With<Scoreboard>>,
+    audio: Res<Audio>,
+    audio_handles: Res<AudioHandles>,
+) {
+    for NewHighScore(previous) in event_reader.iter() {
+        toast_writer.send(Toast::new(format!("New high score! Beat {previous}")));
+        burst_writer.send(ParticleBurst {
+            count: 60,
+            speed: 2.0,
+        });
+        for entity in scoreboards.iter() {
+            commands
+                .entity(entity)
+                .insert(ScoreboardGlow(Timer::from_seconds(
+                    HIGH_SCORE_GLOW_SECONDS,
+                    TimerMode::Once,
+                )));
+        }
+
+        // All four tones, and again an octave up
+        let tones = [
+            &audio_handles.red,
+            &audio_handles.green,
+            &audio_handles.blue,
+            &audio_handles.yellow,
+        ];
+        for audio_handle in tones.into_iter().flatten() {
+            for speed in [1.0, 2.0] {
+                audio.play_with_settings(
+                    audio_handle.clone(),
+                    PlaybackSettings::ONCE.with_speed(speed).with_volume(0.5),
+                );
+            }
+        }
+    }
+}
+
+/// Fades glowing scoreboards from gold back to the text color
+fn glow_scoreboard(
+    mut commands: Commands,
+    mut scoreboards: Query<(Entity, &mut Text, &mut ScoreboardGlow)>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    for (entity, mut text, mut glow) in scoreboards.iter_mut() {
+        let t = glow.0.tick(time.delta()).percent();
+        let [gold_r, gold_g, gold_b, _] = HIGH_SCORE_GOLD.as_rgba_f32();
+        let [r, g, b, _] = settings.ui_text_color.color().as_rgba_f32();
+        let color = Color::rgb(
+            gold_r + (r - gold_r) * t,
+            gold_g + (g - gold_g) * t,
+            gold_b + (b - gold_b) * t,
+        );
+        for section in text.sections.iter_mut() {
+            section.style.color = color;
+        }
+        if glow.0.finished() {
+            commands.entity(entity).remove::<ScoreboardGlow>();
+        }
+    }
+}
+
+/// Spawns particles out of every button in its color, unless motion is reduced
+///
+/// Performance mode spawns fewer of them
+fn spawn_particle_bursts(
+    mut commands: Commands,
+    mut event_reader: EventReader<ParticleBurst>,
+    buttons: Query<(&GlobalTransform, &Handle<StandardMaterial>), With<Button>>,
+    particle_mesh: Res<ParticleMesh>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    settings: Res<Settings>,
+) {
+    if settings.reduced_motion {
+        event_reader.clear();
+        return;
+    }
+    let mut rng = rand::thread_rng();
+    for burst in event_reader.iter() {
+        let mut particle_materials = HashMap::new();
+        for (transform, material_handle) in buttons.iter() {
+            let material = particle_materials
+                .entry(material_handle.id())
+                .or_insert_with(|| {
+                    let color = materials
+                        .get(material_handle)
+                        .map_or(Color::WHITE, |material| material.base_color);
+                    materials.add(StandardMaterial {
+                        base_color: color,
+                        emissive: color,
+                        unlit: true,
+                        ..Default::default()
+                    })
+                })
+                .clone();
+
+            let count = if settings.performance_mode {
+                (burst.count / PERFORMANCE_PARTICLE_DIVISOR).max(1)
+            } else {
+                burst.count
+            };
+            for _ in 0..count {
+                let angle = rng.gen_range(0.0..TAU);
+                let spread = rng.gen_range(0.2..1.0);
+                let velocity = Vec3::new(angle.cos() * spread, 1.5, angle.sin() * spread)
+                    * burst.speed
+                    * rng.gen_range(0.5..1.0);
+                commands
+                    .spawn(PbrBundle {
+                        mesh: particle_mesh.0.clone(),
+                        material: material.clone(),
+                        transform: Transform::from_translation(transform.translation()),
+                        ..Default::default()
+                    })
+                    .insert(Particle { velocity })
+                    .insert(Lifetime(Timer::from_seconds(
+                        rng.gen_range(0.8..1.4),
+                        TimerMode::Once,
+                    )))
+                    .insert(NotShadowCaster);
+            }
+        }
+    }
+}
+
+/// Moves and shrinks particles
+fn update_particles(
+    mut particles: Query<(&mut Particle, &Lifetime, &mut Transform)>,
+    time: Res<Time>,
+) {
+    for (mut particle, lifetime, mut transform) in particles.iter_mut() {
+        particle.velocity.y += PARTICLE_GRAVITY * time.delta_seconds();
+        transform.translation += particle.velocity * time.delta_seconds();
+        transform.scale = Vec3::splat(lifetime.0.percent_left());
+    }
+}
+
+/// Ticks `Lifetime`s and despawns expired entities
+fn despawn_expired(
+    mut commands: Commands,
+    mut lifetimes: Query<(Entity, &mut Lifetime)>,
+    time: Res<Time>,
+) {
+    for (entity, mut lifetime) in lifetimes.iter_mut() {
+        if lifetime.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Spawns a "+N" over the last pressed button when points are awarded
+#[allow(clippy::too_many_arguments)]
+fn spawn_score_popups(
+    mut commands: Commands,
+    mut points_reader: EventReader<PointsAwarded>,
+    mut button_reader: EventReader<ButtonEvent>,
+    mut last_pressed: Local<Option<Button>>,
+    buttons: Query<(&Button, &GlobalTransform)>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    font: Res<UiFont>,
+    settings: Res<Settings>,
+) {
+    for event in button_reader.iter() {
+        if let ButtonEvent::Pressed(button) = event {
+            *last_pressed = Some(*button);
+        }
+    }
+
+    let popups: Vec<_> = points_reader
+        .iter()
+        // Rounds that score nothing, like peeked ones, don't get a popup
+        .filter(|PointsAwarded(points)| *points > 0)
+        .map(|PointsAwarded(points)| format!("+{points}"))
+        .collect();
+    if popups.is_empty() {
+        return;
+    }
+
+    let Ok((camera, camera_transform)) = cameras.get_single() else {
+        return;
+    };
+    let Some(position) = buttons
+        .iter()
+        .find(|(button, _)| Some(**button) == *last_pressed)
+        .and_then(|(_, transform)| {
+            camera.world_to_viewport(camera_transform, transform.translation())
+        })
+    else {
+        return;
+    };
+
+    for label in popups {
+        commands
+            .spawn(
+                TextBundle::from_section(
+                    label,
+                    TextStyle {
+                        font: font.handle.clone(),
+                        font_size: 48.0,
+                        color: settings.ui_text_color.color(),
+                    },
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Px(position.x),
+                        bottom: Val::Px(position.y),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }),
+            )
+            .insert(FloatingText { speed: 60.0 })
+            .insert(Lifetime(Timer::from_seconds(1.0, TimerMode::Once)));
+    }
+}
+
+/// Rises and fades `FloatingText`, only fading if motion is reduced
+fn float_text(
+    mut texts: Query<(&FloatingText, &Lifetime, &mut Style, &mut Text)>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    for (floating, lifetime, mut style, mut text) in texts.iter_mut() {
+        if let Val::Px(bottom) = style.position.bottom {
+            if !settings.reduced_motion {
+                style.position.bottom = Val::Px(bottom + floating.speed * time.delta_seconds());
+            }
+        }
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(lifetime.0.percent_left());
+        }
+    }
+}
+
+/// Briefly brightens the lamp after completing a pattern
+fn flash_on_success(
+    mut commands: Commands,
+    mut event_reader: EventReader<SimonEvent>,
+    lamps: Query<(Entity, &SpotLight, Option<&LampFlash>), With<Lamp>>,
+) {
+    if event_reader
+        .iter()
+        .any(|event| *event == SimonEvent::Success)
+    {
+        // Milestones flash harder, so don't interrupt one
+        for (entity, spot_light, _) in lamps.iter().filter(|(.., flash)| flash.is_none()) {
+            commands.entity(entity).insert(LampFlash {
+                timer: Timer::from_seconds(0.5, TimerMode::Once),
+                base_intensity: spot_light.intensity,
+                boost: 1.5,
+            });
+        }
+    }
+}
+
+/// Briefly dims the lamp on each beat of the demo, as much as the beat pulse
+/// setting asks for
+///
+/// Beats come from `show_button`, so the pulse follows the demo at any speed
+fn pulse_on_beat(
+    mut commands: Commands,
+    mut event_reader: EventReader<ButtonEvent>,
+    settings: Res<Settings>,
+    lamps: Query<(Entity, &SpotLight, Option<&LampFlash>), With<Lamp>>,
+) {
+    let beat = event_reader
+        .iter()
+        .any(|event| matches!(event, ButtonEvent::Lit(_)));
+    let Some((seconds, dim)) = settings.beat_pulse.envelope() else {
+        return;
+    };
+    if !beat {
+        return;
+    }
+    for (entity, spot_light, _) in lamps.iter().filter(|(.., flash)| flash.is_none()) {
+        commands.entity(entity).insert((
+            LampFlash {
+                timer: Timer::from_seconds(seconds, TimerMode::Once),
+                base_intensity: spot_light.intensity,
+                boost: -dim,
+            },
+            BeatDip,
+        ));
+    }
+}
+
+/// Puts the lamp back to full brightness when the player's turn starts, so a
+/// beat from the end of the demo doesn't dim it
+fn steady_lamp(
+    mut commands: Commands,
+    mut lamps: Query<(Entity, &mut SpotLight, &LampFlash), With<BeatDip>>,
+) {
+    for (entity, mut spot_light, flash) in lamps.iter_mut() {
+        spot_light.intensity = flash.base_intensity;
+        commands.entity(entity).remove::<(LampFlash, BeatDip)>();
+    }
+}
+
+/// Fades the lamp back from a flash
+fn flash_lamp(
+    mut commands: Commands,
+    mut lamps: Query<(Entity, &mut SpotLight, &mut LampFlash)>,
+    time: Res<Time>,
+) {
+    for (entity, mut spot_light, mut flash) in lamps.iter_mut() {
+        flash.timer.tick(time.delta());
+        if flash.timer.finished() {
+            spot_light.intensity = flash.base_intensity;
+            commands.entity(entity).remove::<(LampFlash, BeatDip)>();
+        } else {
+            let boost = 1.0 + flash.boost * flash.timer.percent_left();
+            spot_light.intensity = flash.base_intensity * boost;
+        }
+    }
+}
+
+/// Briefly slows down time after completing a pattern
+///
+/// The fixed timestep and every timer use the scaled time, so the next round
+/// is delayed along with everything else rather than getting out of sync.
+fn slow_motion(
+    mut event_reader: EventReader<SimonEvent>,
+    mut time: ResMut<Time>,
+    mut timer: Local<Option<Timer>>,
+    settings: Res<Settings>,
+    game_mode: Res<GameMode>,
+) {
+    let success = event_reader
+        .iter()
+        .any(|event| *event == SimonEvent::Success);
+    // Classic hardware keeps the original's timings
+    if success && !settings.reduced_motion && *game_mode != GameMode::Hardware {
+        time.set_relative_speed(SLOW_MOTION_SPEED);
+        *timer = Some(Timer::from_seconds(SLOW_MOTION_SECONDS, TimerMode::Once));
+    }
+
+    if let Some(slow_motion) = &mut *timer {
+        // Real time, otherwise the slow-mo would slow itself down
+        if slow_motion.tick(time.raw_delta()).finished() {
+            time.set_relative_speed(1.0);
+            *timer = None;
+        }
+    }
+}
+
+/// Starts shaking the button that was wrongly pressed
+fn start_shakes(
+    mut commands: Commands,
+    mut event_reader: EventReader<WrongPress>,
+    mut buttons: Query<(Entity, &Button, &Transform, Option<&mut Shake>)>,
+) {
+    for WrongPress(wrong) in event_reader.iter() {
+        for (entity, button, transform, shake) in buttons.iter_mut() {
+            if button != wrong {
+                continue;
+            }
+            if let Some(mut shake) = shake {
+                shake.timer.reset();
+            } else {
+                commands.entity(entity).insert(Shake {
+                    timer: Timer::from_seconds(SHAKE_SECONDS, TimerMode::Once),
+                    origin_x: transform.translation.x,
+                });
+            }
+        }
+    }
+}
+
+/// Wobbles shaking buttons side to side, putting them back when done
+///
+/// With reduced motion they only turn red
+fn shake_buttons(
+    mut commands: Commands,
+    mut buttons: Query<(
+        Entity,
+        &mut Shake,
+        &ButtonState,
+        &mut Transform,
+        &Handle<StandardMaterial>,
+    )>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    for (entity, mut shake, state, mut transform, material_handle) in buttons.iter_mut() {
+        let Some(material) = materials.get_mut(material_handle) else {
+            continue;
+        };
+        if shake.timer.tick(time.delta()).finished() {
+            transform.translation.x = shake.origin_x;
+            if matches!(state, ButtonState::Inactive) {
+                material.emissive = Color::BLACK;
+            }
+            commands.entity(entity).remove::<Shake>();
+        } else if settings.reduced_motion {
+            transform.translation.x = shake.origin_x;
+            material.emissive = Color::RED;
+        } else {
+            let elapsed = shake.timer.elapsed_secs();
+            let offset = (elapsed * SHAKE_FREQUENCY * TAU).sin()
+                * SHAKE_DISTANCE
+                * shake.timer.percent_left();
+            transform.translation.x = shake.origin_x + offset;
+            material.emissive = Color::RED;
+        }
+    }
+}
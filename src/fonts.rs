@@ -0,0 +1,108 @@
+use bevy::{asset::LoadState, prelude::*};
+
+use crate::settings::Settings;
+
+/// Loads the UI font, falling back to a built-in one if it's missing, and
+/// colors UI text
+pub struct FontsPlugin;
+
+impl Plugin for FontsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system_to_stage(StartupStage::PreStartup, load_fonts)
+            .add_system(fall_back_on_missing_font)
+            .add_system(apply_text_color);
+    }
+}
+
+pub const UI_FONT_PATH: &str = "fonts/comic.ttf";
+
+/// Built into the binary so text still shows up if the assets are packaged wrong
+const FALLBACK_FONT: &[u8] = include_bytes!("../assets/fonts/FiraMono-Medium.ttf");
+
+/// Marks text in the `UiTextColor` that can be on screen while the setting
+/// changes, so it's recolored to match
+///
+/// Other UI text takes the color when it's spawned
+#[derive(Component)]
+pub struct ThemedText;
+
+/// The font used for all UI text
+#[derive(Resource)]
+pub struct UiFont {
+    pub handle: Handle<Font>,
+    /// Built in, so it can be used before the UI font has loaded
+    pub fallback: Handle<Font>,
+    /// Whether the custom font has finished loading or failed
+    settled: bool,
+}
+
+impl UiFont {
+    /// Whether the UI font is ready to use, or the fallback has replaced it
+    pub fn settled(&self) -> bool {
+        self.settled
+    }
+}
+
+/// Starts loading the UI font and adds the fallback
+fn load_fonts(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut fonts: ResMut<Assets<Font>>,
+) {
+    let fallback = fonts
+        .add(Font::try_from_bytes(FALLBACK_FONT.to_vec()).expect("the fallback font is valid"));
+    commands.insert_resource(UiFont {
+        handle: asset_server.load(UI_FONT_PATH),
+        fallback,
+        settled: false,
+    });
+}
+
+/// Swaps every text over to the fallback font if the UI font fails to load
+fn fall_back_on_missing_font(
+    mut ui_font: ResMut<UiFont>,
+    asset_server: Res<AssetServer>,
+    mut texts: Query<&mut Text>,
+) {
+    if ui_font.settled {
+        return;
+    }
+    match asset_server.get_load_state(&ui_font.handle) {
+        LoadState::Loaded => ui_font.settled = true,
+        LoadState::Failed => {
+            warn!("Couldn't load {UI_FONT_PATH}, using the built-in font instead");
+            let missing = ui_font.handle.clone();
+            ui_font.handle = ui_font.fallback.clone();
+            ui_font.settled = true;
+            for mut text in texts.iter_mut() {
+                for section in text.sections.iter_mut() {
+                    if section.style.font == missing {
+                        section.style.font = ui_font.handle.clone();
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Recolors `ThemedText` when the `UiTextColor` changes, keeping its alpha
+fn apply_text_color(settings: Res<Settings>, mut texts: Query<&mut Text, With<ThemedText>>) {
+    if !settings.is_changed() {
+        return;
+    }
+    let color = settings.ui_text_color.color();
+    for mut text in texts.iter_mut() {
+        for section in text.sections.iter_mut() {
+            let recolored = with_alpha(color, section.style.color.a());
+            if section.style.color != recolored {
+                section.style.color = recolored;
+            }
+        }
+    }
+}
+
+fn with_alpha(mut color: Color, alpha: f32) -> Color {
+    color.set_a(alpha);
+    color
+}
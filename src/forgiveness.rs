@@ -0,0 +1,36 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{settings::Settings, SimonState};
+
+/// Lets the player make a few wrong presses each round before it fails
+pub struct ForgivenessPlugin;
+
+impl Plugin for ForgivenessPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RoundForgiveness>()
+            .add_enter_system(SimonState::MonkeySee, reset_forgiveness);
+    }
+}
+
+/// Wrong presses left to ignore this round, refilled from the setting when
+/// each round starts
+#[derive(Resource, Default)]
+pub struct RoundForgiveness(pub usize);
+
+impl RoundForgiveness {
+    /// Uses up one forgiveness if there's any left, returning whether the
+    /// wrong press is ignored
+    pub fn try_forgive(&mut self) -> bool {
+        if self.0 == 0 {
+            return false;
+        }
+        self.0 -= 1;
+        true
+    }
+}
+
+/// Starts each round with the full allowance
+fn reset_forgiveness(mut forgiveness: ResMut<RoundForgiveness>, settings: Res<Settings>) {
+    forgiveness.0 = settings.forgiveness;
+}
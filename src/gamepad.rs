@@ -0,0 +1,140 @@
+use bevy::prelude::*;
+use gilrs::{
+    ff::{BaseEffect, BaseEffectType, Effect, EffectBuilder, Repeat, Replay, Ticks},
+    Gilrs,
+};
+use iyes_loopless::prelude::*;
+
+use crate::{
+    settings::Settings, AppState, Button, ButtonEvent, PressCooldown, SimonEvent, SimonState,
+};
+
+/// Gamepad input and rumble
+pub struct GamepadPlugin;
+
+impl Plugin for GamepadPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            gamepad_press_buttons
+                .run_in_state(AppState::Playing)
+                .run_in_state(SimonState::MonkeyDo),
+        )
+        .add_system(rumble);
+    }
+}
+
+/// Face buttons, matched to the colors on most controllers
+pub const GAMEPAD_BUTTONS: [(GamepadButtonType, Button); 4] = [
+    (GamepadButtonType::East, Button::Red),
+    (GamepadButtonType::South, Button::Green),
+    (GamepadButtonType::West, Button::Blue),
+    (GamepadButtonType::North, Button::Yellow),
+];
+
+/// Whether a gamepad was already holding `button` down before this frame
+pub fn held_on_gamepad(
+    gamepads: &Gamepads,
+    gamepad_buttons: &Input<GamepadButton>,
+    button: Button,
+) -> bool {
+    GAMEPAD_BUTTONS
+        .into_iter()
+        .filter(|(_, other)| *other == button)
+        .any(|(button_type, _)| {
+            gamepads.iter().any(|gamepad| {
+                let gamepad_button = GamepadButton::new(gamepad, button_type);
+                gamepad_buttons.pressed(gamepad_button)
+                    && !gamepad_buttons.just_pressed(gamepad_button)
+            })
+        })
+}
+
+/// Fires button events when gamepad face buttons are pressed
+///
+/// Like clicks, a button only presses when it goes down, so it isn't pressed
+/// again while another gamepad or the mouse is still holding it
+pub fn gamepad_press_buttons(
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut buttons: Query<(&Button, &Interaction, &mut PressCooldown)>,
+    mut button_event_writer: EventWriter<ButtonEvent>,
+) {
+    for (button_type, button) in GAMEPAD_BUTTONS {
+        // Pressing it on two gamepads at once still counts once
+        let pressed = gamepads
+            .iter()
+            .any(|gamepad| gamepad_buttons.just_pressed(GamepadButton::new(gamepad, button_type)));
+        if !pressed || held_on_gamepad(&gamepads, &gamepad_buttons, button) {
+            continue;
+        }
+        let ready = buttons
+            .iter_mut()
+            .find(|(other, _, _)| **other == button)
+            .is_none_or(|(_, interaction, mut cooldown)| {
+                *interaction != Interaction::Clicked && cooldown.try_press()
+            });
+        if ready {
+            button_event_writer.send(ButtonEvent::Pressed(button));
+        }
+    }
+}
+
+/// Rumbles every connected gamepad on correct presses and failures
+fn rumble(
+    mut event_reader: EventReader<SimonEvent>,
+    gilrs: Option<NonSendMut<Gilrs>>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+    // Effects stop when dropped, so they're kept until they finish
+    mut playing: Local<Vec<(Effect, Timer)>>,
+) {
+    playing.retain_mut(|(_, timer)| !timer.tick(time.delta()).finished());
+
+    let Some(mut gilrs) = gilrs else {
+        event_reader.clear();
+        return;
+    };
+
+    for event in event_reader.iter() {
+        if !settings.haptics {
+            continue;
+        }
+
+        let (kind, milliseconds) = match event {
+            SimonEvent::Next | SimonEvent::Success => {
+                (BaseEffectType::Weak { magnitude: 20_000 }, 80)
+            }
+            SimonEvent::Failure => (BaseEffectType::Strong { magnitude: 60_000 }, 400),
+        };
+
+        let gamepads: Vec<_> = gilrs
+            .gamepads()
+            .filter(|(_, gamepad)| gamepad.is_ff_supported())
+            .map(|(id, _)| id)
+            .collect();
+        if gamepads.is_empty() {
+            continue;
+        }
+
+        let effect = EffectBuilder::new()
+            .add_effect(BaseEffect {
+                kind,
+                scheduling: Replay {
+                    play_for: Ticks::from_ms(milliseconds),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .gamepads(&gamepads)
+            .repeat(Repeat::For(Ticks::from_ms(milliseconds)))
+            .finish(&mut gilrs);
+
+        match effect.and_then(|effect| effect.play().map(|_| effect)) {
+            Ok(effect) => playing.push((
+                effect,
+                Timer::from_seconds(milliseconds as f32 / 1000.0, TimerMode::Once),
+            )),
+            Err(error) => warn!("Couldn't rumble gamepad: {error}"),
+        }
+    }
+}
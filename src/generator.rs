@@ -0,0 +1,158 @@
+use std::{
+    env,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::Button;
+
+/// Makes every run use the same patterns, for reproducing bugs
+const SEED_FLAG: &str = "--seed";
+/// Makes every run today use the same patterns as everyone else's
+const DAILY_FLAG: &str = "--daily";
+const SECONDS_PER_DAY: u64 = 60 * 60 * 24;
+/// Tries at a different button before giving up on avoiding a repeat
+const MAX_REROLLS: usize = 16;
+
+/// Picks the buttons added to the pattern
+pub trait ButtonGenerator: Send + Sync {
+    /// The button to add after `pattern`
+    fn next(&mut self, pattern: &[Button]) -> Button;
+
+    /// The seed behind the current pattern, if it can be replayed from one
+    fn seed(&self) -> Option<u64> {
+        None
+    }
+
+    /// Like `next`, but re-rolls a button that's the same as the last one
+    fn next_without_repeat(&mut self, pattern: &[Button]) -> Button {
+        let mut button = self.next(pattern);
+        for _ in 0..MAX_REROLLS {
+            if pattern.last() != Some(&button) {
+                break;
+            }
+            button = self.next(pattern);
+        }
+        button
+    }
+}
+
+/// The generator `update_pattern` uses
+#[derive(Resource)]
+pub struct PatternGenerator(pub Box<dyn ButtonGenerator>);
+
+impl PatternGenerator {
+    /// The generator asked for on the command line, or a random one
+    pub fn from_args() -> Self {
+        let mut args = env::args();
+        while let Some(arg) = args.next() {
+            if arg == DAILY_FLAG {
+                return Self(Box::new(SeededGenerator::daily()));
+            }
+            if arg == SEED_FLAG {
+                match args.next().map(|seed| seed.parse()) {
+                    Some(Ok(seed)) => return Self(Box::new(SeededGenerator::new(seed))),
+                    _ => warn!("{SEED_FLAG} needs a number after it, using random patterns"),
+                }
+            }
+        }
+        Self(Box::new(RandomGenerator::default()))
+    }
+}
+
+/// The day a `--daily` run was started on, for sharing the result
+#[derive(Resource)]
+pub struct Daily(pub u64);
+
+impl Daily {
+    /// Today, if daily patterns were asked for on the command line
+    pub fn from_args() -> Option<Self> {
+        env::args()
+            .any(|arg| arg == DAILY_FLAG)
+            .then(|| Self(today()))
+    }
+}
+
+/// Days since 1970-01-01, in UTC
+fn today() -> u64 {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    seconds / SECONDS_PER_DAY
+}
+
+/// A different pattern every time, from a new random seed each run so any
+/// run can be played again
+pub struct RandomGenerator(SeededGenerator);
+
+impl Default for RandomGenerator {
+    fn default() -> Self {
+        Self(SeededGenerator::new(rand::random()))
+    }
+}
+
+impl ButtonGenerator for RandomGenerator {
+    fn next(&mut self, pattern: &[Button]) -> Button {
+        if pattern.is_empty() {
+            *self = Self::default();
+        }
+        self.0.next(pattern)
+    }
+
+    fn seed(&self) -> Option<u64> {
+        self.0.seed()
+    }
+}
+
+/// The same patterns every run for the same seed
+pub struct SeededGenerator {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl SeededGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Seeded with the day, so it changes at midnight UTC
+    pub fn daily() -> Self {
+        Self::new(today())
+    }
+}
+
+impl ButtonGenerator for SeededGenerator {
+    fn next(&mut self, pattern: &[Button]) -> Button {
+        // Every run starts from an empty pattern, so start the sequence over
+        if pattern.is_empty() {
+            self.rng = StdRng::seed_from_u64(self.seed);
+        }
+        self.rng.gen()
+    }
+
+    fn seed(&self) -> Option<u64> {
+        Some(self.seed)
+    }
+}
+
+/// Buttons from a list, like a `Challenge`, starting over if the pattern
+/// gets longer than it
+///
+/// The list can't be empty
+pub struct ListGenerator(pub Vec<Button>);
+
+impl ButtonGenerator for ListGenerator {
+    fn next(&mut self, pattern: &[Button]) -> Button {
+        self.0[pattern.len() % self.0.len()]
+    }
+
+    /// Lists are played as written, repeats and all
+    fn next_without_repeat(&mut self, pattern: &[Button]) -> Button {
+        self.next(pattern)
+    }
+}
@@ -0,0 +1,157 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    challenge::Challenge, menu::MenuAction, review::InputHistory, save::SaveData,
+    settings::Settings, AppState, BoostedStart, Button, ButtonEvent, ButtonState, GameMode,
+    SimonEvent, SimonState,
+};
+
+/// Records the best run and replays it on the main menu
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GhostRecording>()
+            .add_enter_system(SimonState::MonkeyDo, start_recording)
+            .add_system(
+                record_presses
+                    .run_in_state(AppState::Playing)
+                    .run_in_state(SimonState::MonkeyDo),
+            )
+            .add_exit_system(AppState::Menu, stop_ghost)
+            .add_system(start_ghost.run_in_state(AppState::Menu))
+            .add_system(play_ghost.run_in_state(AppState::Menu));
+    }
+}
+
+/// Time before the ghost's first press
+const GHOST_LEAD_IN_SECONDS: f32 = 0.5;
+
+/// The presses from the longest pattern ever repeated in classic mode
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct BestRun {
+    pub presses: Vec<GhostPress>,
+}
+
+/// A button press and when it happened, in seconds since the round started
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct GhostPress {
+    pub button: Button,
+    pub seconds: f32,
+}
+
+/// The presses made so far this round
+#[derive(Resource, Default)]
+struct GhostRecording {
+    started: f32,
+    presses: Vec<GhostPress>,
+}
+
+/// The best run being replayed
+#[derive(Resource)]
+pub struct GhostReplay {
+    presses: Vec<GhostPress>,
+    elapsed: f32,
+}
+
+/// Starts timing a new round
+fn start_recording(mut recording: ResMut<GhostRecording>, time: Res<Time>) {
+    recording.started = time.elapsed_seconds();
+    recording.presses.clear();
+}
+
+/// Timestamps each press validated this round, and keeps the round as the best
+/// run when it's the longest pattern repeated so far
+#[allow(clippy::too_many_arguments)]
+fn record_presses(
+    mut recording: ResMut<GhostRecording>,
+    mut save_data: ResMut<SaveData>,
+    mut event_reader: EventReader<SimonEvent>,
+    history: Res<InputHistory>,
+    game_mode: Res<GameMode>,
+    boosted_start: Res<BoostedStart>,
+    settings: Res<Settings>,
+    challenge: Option<Res<Challenge>>,
+    time: Res<Time>,
+) {
+    let seconds = time.elapsed_seconds() - recording.started;
+    while let Some(button) = history.0.get(recording.presses.len()) {
+        recording.presses.push(GhostPress {
+            button: *button,
+            seconds,
+        });
+    }
+
+    for event in event_reader.iter() {
+        if *event == SimonEvent::Success
+            && *game_mode == GameMode::Classic
+            && !boosted_start.0
+            && !settings.preview_unranked()
+            && challenge.is_none()
+            && recording.presses.len() > save_data.best_run.presses.len()
+        {
+            save_data.best_run.presses = recording.presses.clone();
+        }
+    }
+}
+
+/// Starts replaying the best run when asked to
+fn start_ghost(
+    mut commands: Commands,
+    mut event_reader: EventReader<MenuAction>,
+    save_data: Res<SaveData>,
+) {
+    for event in event_reader.iter() {
+        if *event == MenuAction::WatchBestRun && !save_data.best_run.presses.is_empty() {
+            commands.insert_resource(GhostReplay {
+                presses: save_data.best_run.presses.clone(),
+                elapsed: -GHOST_LEAD_IN_SECONDS,
+            });
+        }
+    }
+}
+
+/// Presses the ghost's buttons at the times they were pressed
+fn play_ghost(
+    mut commands: Commands,
+    replay: Option<ResMut<GhostReplay>>,
+    mut button_event_writer: EventWriter<ButtonEvent>,
+    time: Res<Time>,
+) {
+    let Some(mut replay) = replay else {
+        return;
+    };
+    let previous = replay.elapsed;
+    replay.elapsed += time.delta_seconds();
+    let elapsed = replay.elapsed;
+
+    for press in replay.presses.iter() {
+        if press.seconds > previous && press.seconds <= elapsed {
+            button_event_writer.send(ButtonEvent::Pressed(press.button));
+        }
+    }
+    if replay
+        .presses
+        .last()
+        .is_none_or(|last| last.seconds <= elapsed)
+    {
+        commands.remove_resource::<GhostReplay>();
+    }
+}
+
+/// Stops the replay when leaving the menu
+fn stop_ghost(
+    mut commands: Commands,
+    replay: Option<Res<GhostReplay>>,
+    mut buttons: Query<&mut ButtonState>,
+) {
+    if replay.is_some() {
+        commands.remove_resource::<GhostReplay>();
+        for mut state in buttons.iter_mut() {
+            *state = ButtonState::Inactive;
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use std::time::Duration;
+
+use bevy::{
+    audio::{play_queued_audio_system, AudioOutput, Decodable, Source},
+    prelude::*,
+    reflect::TypeUuid,
+};
+use iyes_loopless::prelude::*;
+
+use crate::{
+    settings::Settings, AppState, Button, ButtonEvent, ButtonTimings, GameMode, Pattern, SimonEvent,
+};
+
+/// Plays like the original 1978 Simon, with its tones, its timing and its
+/// win tune at 31 buttons
+pub struct HardwarePlugin;
+
+impl Plugin for HardwarePlugin {
+    fn build(&self, app: &mut App) {
+        // The tones are generated rather than loaded, so they get their own audio output
+        app.init_non_send_resource::<AudioOutput<Tone>>()
+            .add_asset::<Tone>()
+            .init_resource::<Audio<Tone>>()
+            .add_system_to_stage(CoreStage::PostUpdate, play_queued_audio_system::<Tone>)
+            .add_system(play_button_tones.run_in_state(AppState::Playing))
+            .add_system(play_razz.run_in_state(AppState::Playing))
+            .add_enter_system(AppState::Victory, play_win_tune);
+    }
+}
+
+/// Repeating a pattern this long wins
+pub const HARDWARE_MAX_LENGTH: usize = 31;
+/// How long each demo button sounds, until the first speed-up
+pub const HARDWARE_LIT_DURATION: f32 = 0.42;
+/// The pattern lengths the demo speeds up at, and how long each button sounds from then on
+pub const HARDWARE_SPEED_UPS: [(usize, f32); 3] = [(5, 0.32), (9, 0.22), (13, 0.17)];
+pub const HARDWARE_GAP_DURATION: f32 = 0.05;
+/// A pause after each round before the next demo
+pub const HARDWARE_SUCCESS_DELAY: f32 = 0.8;
+
+const TONE_SAMPLE_RATE: u32 = 44_100;
+/// Square waves are harsh, so they're kept quiet
+const TONE_VOLUME: f32 = 0.15;
+/// The low buzz for a mistake, in hertz and seconds
+const RAZZ: (f32, f32) = (42.0, 1.5);
+/// The win tune runs up through the colors a few times, quickly
+const WIN_TUNE: [Button; 4] = [Button::Blue, Button::Yellow, Button::Red, Button::Green];
+const WIN_TUNE_RUNS: usize = 3;
+const WIN_NOTE_SECONDS: f32 = 0.07;
+
+/// Each button's tone on the original, in hertz
+fn frequency(button: Button) -> f32 {
+    match button {
+        Button::Green => 415.0,
+        Button::Red => 310.0,
+        Button::Yellow => 252.0,
+        Button::Blue => 209.0,
+    }
+}
+
+/// Whether the original's tones play instead of the sound pack
+pub fn uses_hardware_sounds(game_mode: GameMode, app_state: AppState) -> bool {
+    game_mode == GameMode::Hardware && app_state == AppState::Playing
+}
+
+/// Square wave notes played one after another, like the original's speaker
+#[derive(TypeUuid)]
+#[uuid = "eece3b1e-ff67-4bdf-b634-07ff46feb81d"]
+struct Tone {
+    /// Each note's frequency in hertz and length in seconds
+    notes: Vec<(f32, f32)>,
+}
+
+/// Generates a `Tone`
+struct ToneDecoder {
+    notes: Vec<(f32, f32)>,
+    note: usize,
+    sample: u64,
+}
+
+impl Iterator for ToneDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        loop {
+            let (frequency, seconds) = *self.notes.get(self.note)?;
+            let t = self.sample as f32 / TONE_SAMPLE_RATE as f32;
+            if t >= seconds {
+                self.note += 1;
+                self.sample = 0;
+                continue;
+            }
+            self.sample += 1;
+            let high = (t * frequency).fract() < 0.5;
+            return Some(if high { TONE_VOLUME } else { -TONE_VOLUME });
+        }
+    }
+}
+
+impl Source for ToneDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        TONE_SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        let seconds = self.notes.iter().map(|(_, seconds)| seconds).sum();
+        Some(Duration::from_secs_f32(seconds))
+    }
+}
+
+impl Decodable for Tone {
+    type Decoder = ToneDecoder;
+    type DecoderItem = f32;
+
+    fn decoder(&self) -> Self::Decoder {
+        ToneDecoder {
+            notes: self.notes.clone(),
+            note: 0,
+            sample: 0,
+        }
+    }
+}
+
+/// Sounds each button's tone for as long as it's lit or pushed down
+fn play_button_tones(
+    mut event_reader: EventReader<ButtonEvent>,
+    audio: Res<Audio<Tone>>,
+    mut tones: ResMut<Assets<Tone>>,
+    game_mode: Res<GameMode>,
+    timings: Res<ButtonTimings>,
+    pattern: Res<Pattern>,
+    settings: Res<Settings>,
+) {
+    if *game_mode != GameMode::Hardware {
+        event_reader.clear();
+        return;
+    }
+    for event in event_reader.iter() {
+        let (button, seconds) = match event {
+            ButtonEvent::Lit(button) => (button, timings.lit(pattern.0.len(), &settings)),
+            ButtonEvent::Pressed(button) => (button, timings.pressed(settings.game_speed)),
+        };
+        audio.play(tones.add(Tone {
+            notes: vec![(frequency(*button), seconds)],
+        }));
+    }
+}
+
+/// Buzzes on a mistake
+fn play_razz(
+    mut event_reader: EventReader<SimonEvent>,
+    audio: Res<Audio<Tone>>,
+    mut tones: ResMut<Assets<Tone>>,
+    game_mode: Res<GameMode>,
+) {
+    let failed = event_reader
+        .iter()
+        .any(|event| *event == SimonEvent::Failure);
+    event_reader.clear();
+    if failed && *game_mode == GameMode::Hardware {
+        audio.play(tones.add(Tone { notes: vec![RAZZ] }));
+    }
+}
+
+/// Plays the win tune after repeating the longest pattern
+fn play_win_tune(
+    audio: Res<Audio<Tone>>,
+    mut tones: ResMut<Assets<Tone>>,
+    game_mode: Res<GameMode>,
+) {
+    if *game_mode != GameMode::Hardware {
+        return;
+    }
+    let notes = WIN_TUNE
+        .iter()
+        .cycle()
+        .take(WIN_TUNE.len() * WIN_TUNE_RUNS)
+        .map(|button| (frequency(*button), WIN_NOTE_SECONDS))
+        .collect();
+    audio.play(tones.add(Tone { notes }));
+}
@@ -0,0 +1,116 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{
+    fonts::UiFont, preview::PatternPreview, settings::Settings, AppState, ButtonEvent, Pattern,
+    SimonState,
+};
+
+/// Reminds idle players what to do while they're repeating the pattern
+pub struct HintPlugin;
+
+impl Plugin for HintPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_enter_system(SimonState::MonkeyDo, start_idle_timer)
+            .add_exit_system(SimonState::MonkeyDo, end_idle_timer)
+            .add_system(
+                show_idle_hint
+                    .run_in_state(AppState::Playing)
+                    .run_in_state(SimonState::MonkeyDo)
+                    .run_if_resource_exists::<IdleTimer>()
+                    .run_unless_resource_exists::<PatternPreview>(),
+            );
+    }
+}
+
+/// How long the player can sit still before the hint shows up
+const IDLE_HINT_SECONDS: f32 = 6.0;
+
+/// Time since the player last did anything this round
+#[derive(Resource)]
+struct IdleTimer(Timer);
+
+/// Marker component for the hint
+#[derive(Component)]
+struct IdleHint;
+
+/// Starts timing the round, if hints are on
+fn start_idle_timer(mut commands: Commands, settings: Res<Settings>) {
+    if settings.idle_hint {
+        commands.insert_resource(IdleTimer(Timer::from_seconds(
+            IDLE_HINT_SECONDS,
+            TimerMode::Once,
+        )));
+    }
+}
+
+/// Shows the hint once the player has been idle for a while, and hides it
+/// again on any input
+#[allow(clippy::too_many_arguments)]
+fn show_idle_hint(
+    mut commands: Commands,
+    mut idle_timer: ResMut<IdleTimer>,
+    mut button_event_reader: EventReader<ButtonEvent>,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    touches: Res<Touches>,
+    hints: Query<Entity, With<IdleHint>>,
+    font: Res<UiFont>,
+    pattern: Res<Pattern>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    let pressed = button_event_reader
+        .iter()
+        .any(|event| matches!(event, ButtonEvent::Pressed(_)));
+    if pressed
+        || keys.get_just_pressed().next().is_some()
+        || mouse_buttons.get_just_pressed().next().is_some()
+        || gamepad_buttons.get_just_pressed().next().is_some()
+        || touches.iter_just_pressed().next().is_some()
+    {
+        idle_timer.0.reset();
+        for entity in hints.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+
+    if idle_timer.0.tick(time.delta()).just_finished() {
+        let hint = if settings.reverses_input(pattern.0.len()) {
+            "Press the buttons in reverse order!"
+        } else {
+            "Press the buttons in the order you saw!"
+        };
+        commands
+            .spawn(
+                TextBundle::from_section(
+                    hint,
+                    TextStyle {
+                        font: font.handle.clone(),
+                        font_size: 32.0,
+                        color: settings.ui_text_color.color(),
+                    },
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        top: Val::Percent(12.0),
+                        left: Val::Percent(2.0),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }),
+            )
+            .insert(IdleHint);
+    }
+}
+
+/// Stops timing and hides the hint when the round ends
+fn end_idle_timer(mut commands: Commands, hints: Query<Entity, With<IdleHint>>) {
+    commands.remove_resource::<IdleTimer>();
+    for entity in hints.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{settings::Settings, AppState, AudioHandles, Button, SimonState};
+
+/// Quietly plays a button's sound when the cursor rests on it, if enabled
+pub struct HoverPreviewPlugin;
+
+impl Plugin for HoverPreviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            preview_hovered_button
+                .run_in_state(AppState::Playing)
+                .run_in_state(SimonState::MonkeyDo),
+        );
+    }
+}
+
+/// How long the cursor has to rest on a button before its sound plays,
+/// so sweeping across the buttons stays quiet
+const HOVER_PREVIEW_DELAY: f32 = 0.2;
+const HOVER_PREVIEW_VOLUME: f32 = 0.3;
+
+/// The button under the cursor, when it got there and whether it's been heard
+struct Hover {
+    button: Button,
+    since: f32,
+    played: bool,
+}
+
+/// Plays the sound of the button the cursor is resting on, once per hover
+///
+/// Pressing the button counts as hearing it, so letting go doesn't play it again
+fn preview_hovered_button(
+    interactions: Query<(&Interaction, &Button)>,
+    settings: Res<Settings>,
+    audio: Res<Audio>,
+    audio_handles: Res<AudioHandles>,
+    time: Res<Time>,
+    mut hover: Local<Option<Hover>>,
+) {
+    if !settings.hover_preview {
+        return;
+    }
+    let now = time.elapsed_seconds();
+    let Some((interaction, button)) = interactions
+        .iter()
+        .find(|(interaction, _)| **interaction != Interaction::None)
+    else {
+        *hover = None;
+        return;
+    };
+
+    let hover = match &mut *hover {
+        Some(hover) if hover.button == *button => hover,
+        hover => hover.insert(Hover {
+            button: *button,
+            since: now,
+            played: false,
+        }),
+    };
+    if *interaction == Interaction::Clicked {
+        hover.played = true;
+    }
+    if hover.played || now - hover.since < HOVER_PREVIEW_DELAY {
+        return;
+    }
+    hover.played = true;
+    let handle = match button {
+        Button::Red => &audio_handles.red,
+        Button::Green => &audio_handles.green,
+        Button::Blue => &audio_handles.blue,
+        Button::Yellow => &audio_handles.yellow,
+    };
+    if let Some(handle) = handle {
+        audio.play_with_settings(
+            handle.clone(),
+            PlaybackSettings::ONCE.with_volume(HOVER_PREVIEW_VOLUME),
+        );
+    }
+}
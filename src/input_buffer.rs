@@ -0,0 +1,87 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{
+    gamepad::GAMEPAD_BUTTONS, preview::PatternPreview, settings::Settings, AppState, Button,
+    ButtonEvent, Pattern, Progress, SimonState,
+};
+
+/// Queues presses made just before `MonkeyDo`, if enabled
+pub struct InputBufferPlugin;
+
+impl Plugin for InputBufferPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputBuffer>()
+            .add_enter_system(SimonState::MonkeySee, clear_input_buffer)
+            .add_system(
+                buffer_presses
+                    .run_in_state(AppState::Playing)
+                    .run_in_state(SimonState::MonkeySee),
+            )
+            .add_system(
+                replay_buffered_presses
+                    .run_in_state(AppState::Playing)
+                    .run_in_state(SimonState::MonkeyDo)
+                    .run_unless_resource_exists::<PatternPreview>(),
+            );
+    }
+}
+
+/// Most presses that can be queued
+const INPUT_BUFFER_SIZE: usize = 4;
+
+/// Presses waiting for `MonkeyDo`
+#[derive(Resource, Default)]
+struct InputBuffer(VecDeque<Button>);
+
+/// Throws away presses left over from the last round
+fn clear_input_buffer(mut buffer: ResMut<InputBuffer>) {
+    buffer.0.clear();
+}
+
+/// Queues clicks and gamepad presses once the last demo button has been shown
+fn buffer_presses(
+    mut buffer: ResMut<InputBuffer>,
+    settings: Res<Settings>,
+    pattern: Res<Pattern>,
+    progress: Res<Progress>,
+    interactions: Query<(&Interaction, &Button), Changed<Interaction>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+) {
+    if !settings.buffer_input || progress.0 < pattern.0.len() {
+        return;
+    }
+
+    let clicks = interactions
+        .iter()
+        .filter(|(interaction, _)| **interaction == Interaction::Clicked)
+        .map(|(_, button)| *button);
+    let gamepad_buttons = &*gamepad_buttons;
+    let gamepad_presses = gamepads.iter().flat_map(|gamepad| {
+        GAMEPAD_BUTTONS
+            .into_iter()
+            .filter(move |(button_type, _)| {
+                gamepad_buttons.just_pressed(GamepadButton::new(gamepad, *button_type))
+            })
+            .map(|(_, button)| button)
+    });
+
+    for button in clicks.chain(gamepad_presses) {
+        if buffer.0.len() < INPUT_BUFFER_SIZE {
+            buffer.0.push_back(button);
+        }
+    }
+}
+
+/// Presses the queued buttons, one per frame so each is validated
+fn replay_buffered_presses(
+    mut buffer: ResMut<InputBuffer>,
+    mut button_event_writer: EventWriter<ButtonEvent>,
+) {
+    if let Some(button) = buffer.0.pop_front() {
+        button_event_writer.send(ButtonEvent::Pressed(button));
+    }
+}
@@ -0,0 +1,123 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{
+    pbr::{NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+};
+
+use crate::{settings::Settings, Button};
+
+/// Letters on top of each button, for telling them apart without color
+pub struct ButtonLettersPlugin;
+
+impl Plugin for ButtonLettersPlugin {
+    fn build(&self, app: &mut App) {
+        // The buttons are spawned in `setup`, so wait for them
+        app.add_startup_system_to_stage(StartupStage::PostStartup, setup_button_letters)
+            .add_system(apply_button_letters);
+    }
+}
+
+/// How much of the button's top the letter covers
+const LETTER_SIZE: f32 = 0.5;
+/// Just above the top of the button, so it doesn't flicker into it
+const LETTER_HEIGHT: f32 = 0.501;
+/// Dark enough to read on every button, lit or not
+const LETTER_COLOR: Color = Color::rgb(0.05, 0.05, 0.05);
+
+/// Marker component for a button's letter
+#[derive(Component)]
+struct ButtonLetter;
+
+/// The letter's pixels, from the top row down
+fn letter_pixels(button: Button) -> [&'static str; 5] {
+    match button {
+        Button::Red => ["####.", "#...#", "####.", "#..#.", "#...#"],
+        Button::Green => [".####", "#....", "#..##", "#...#", ".###."],
+        Button::Blue => ["####.", "#...#", "####.", "#...#", "####."],
+        Button::Yellow => ["#...#", ".#.#.", "..#..", "..#..", "..#.."],
+    }
+}
+
+/// A flat mesh with a square for every pixel, facing +Z and centered on the origin
+fn letter_mesh(rows: [&str; 5]) -> Mesh {
+    let pixel = LETTER_SIZE / rows.len() as f32;
+    let mut positions = Vec::new();
+    let mut indices = Vec::new();
+    for (row, line) in rows.iter().enumerate() {
+        for (column, _) in line.chars().enumerate().filter(|(_, c)| *c == '#') {
+            let left = column as f32 * pixel - LETTER_SIZE / 2.0;
+            let top = LETTER_SIZE / 2.0 - row as f32 * pixel;
+            let first = positions.len() as u32;
+            positions.extend([
+                [left, top - pixel, 0.0],
+                [left + pixel, top - pixel, 0.0],
+                [left + pixel, top, 0.0],
+                [left, top, 0.0],
+            ]);
+            indices.extend([first, first + 1, first + 2, first, first + 2, first + 3]);
+        }
+    }
+    let normals = vec![[0.0, 0.0, 1.0]; positions.len()];
+    let uvs = vec![[0.0, 0.0]; positions.len()];
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+/// Gives every button its letter, hidden unless the setting is on
+///
+/// They're children of the buttons, so they sink, glow and scale along with them
+fn setup_button_letters(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    buttons: Query<(Entity, &Button)>,
+    settings: Res<Settings>,
+) {
+    // Unlit, so it stays dark when the button glows
+    let material = materials.add(StandardMaterial {
+        base_color: LETTER_COLOR,
+        unlit: true,
+        ..Default::default()
+    });
+    for (entity, button) in buttons.iter() {
+        let letter = commands
+            .spawn(PbrBundle {
+                mesh: meshes.add(letter_mesh(letter_pixels(*button))),
+                material: material.clone(),
+                // Lying on top of the button, reading away from the camera
+                transform: Transform::from_xyz(0.0, LETTER_HEIGHT, 0.0)
+                    .with_rotation(Quat::from_rotation_x(-FRAC_PI_2)),
+                visibility: Visibility {
+                    is_visible: settings.button_letters,
+                },
+                ..Default::default()
+            })
+            .insert(ButtonLetter)
+            .insert(NotShadowCaster)
+            .insert(NotShadowReceiver)
+            .id();
+        commands.entity(entity).add_child(letter);
+    }
+}
+
+/// Shows or hides the letters when the setting changes
+fn apply_button_letters(
+    settings: Res<Settings>,
+    mut letters: Query<&mut Visibility, With<ButtonLetter>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    for mut visibility in letters.iter_mut() {
+        if visibility.is_visible != settings.button_letters {
+            visibility.is_visible = settings.button_letters;
+        }
+    }
+}
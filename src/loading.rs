@@ -0,0 +1,72 @@
+use bevy::{asset::LoadState, prelude::*};
+use iyes_loopless::prelude::*;
+
+use crate::{fonts::UiFont, menu::fullscreen_column, settings::Settings, AppState, AudioHandles};
+
+/// Holds off on the main menu until the sounds and the UI font have loaded,
+/// so the first press is never silent
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_enter_system(AppState::Loading, spawn_loading_screen)
+            .add_exit_system(AppState::Loading, despawn_loading_screen)
+            .add_system(finish_loading.run_in_state(AppState::Loading));
+    }
+}
+
+/// Marker component for the loading screen
+#[derive(Component)]
+struct LoadingScreen;
+
+/// Shows "Loading…" in the built-in font, since the UI font isn't ready yet
+fn spawn_loading_screen(mut commands: Commands, font: Res<UiFont>, settings: Res<Settings>) {
+    commands
+        .spawn(fullscreen_column())
+        .insert(LoadingScreen)
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Loading…",
+                TextStyle {
+                    font: font.fallback.clone(),
+                    font_size: 48.0,
+                    color: settings.ui_text_color.color(),
+                },
+            ));
+        });
+}
+
+fn despawn_loading_screen(mut commands: Commands, screens: Query<Entity, With<LoadingScreen>>) {
+    for entity in screens.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Goes to the main menu once every sound and the UI font have loaded
+///
+/// Assets that failed to load count as done, since they have fallbacks
+fn finish_loading(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    audio_handles: Res<AudioHandles>,
+    font: Res<UiFont>,
+) {
+    let sounds = [
+        &audio_handles.red,
+        &audio_handles.green,
+        &audio_handles.blue,
+        &audio_handles.yellow,
+        &audio_handles.incorrect,
+    ];
+    let sounds_done = sounds.into_iter().all(|handle| {
+        handle.as_ref().is_some_and(|handle| {
+            matches!(
+                asset_server.get_load_state(handle),
+                LoadState::Loaded | LoadState::Failed
+            )
+        })
+    });
+    if sounds_done && font.settled() {
+        commands.insert_resource(NextState(AppState::Menu));
+    }
+}
@@ -1,19 +1,105 @@
 #![windows_subsystem = "windows"]
 
-use std::{f32::consts::PI, mem, time::Duration};
+mod adaptive;
+mod attract;
+mod backdrop;
+mod camera;
+mod challenge;
+mod combo;
+mod composer;
+mod debug;
+mod desk;
+mod effects;
+mod fonts;
+mod forgiveness;
+mod gamepad;
+mod generator;
+mod ghost;
+mod hardware;
+mod hint;
+mod hover;
+mod input_buffer;
+mod letters;
+mod loading;
+mod menu;
+mod panning;
+mod peek;
+mod preview;
+mod remaining;
+mod reverse;
+mod review;
+mod save;
+mod seed;
+mod settings;
+mod shapes;
+mod share;
+mod toast;
+mod touch;
+mod transition;
+mod turn_timer;
+mod warmup;
+mod zen;
 
+use std::{mem, time::Duration};
+
+use adaptive::Performance;
+use attract::AttractPlugin;
+use backdrop::BackdropPlugin;
 use bevy::{
+    asset::LoadState,
     core_pipeline::bloom::BloomSettings,
     prelude::{shape::Box, *},
+    time::Stopwatch,
     ui::FocusPolicy,
-    window::close_on_esc,
 };
 
 #[cfg(feature = "inspector")]
 use bevy_inspector_egui::WorldInspectorPlugin;
 use bevy_mod_picking::{DefaultPickingPlugins, PickableMesh, PickingCameraBundle};
+use camera::CameraControlPlugin;
+use challenge::{Challenge, ChallengePlugin};
+use combo::ComboPlugin;
+use composer::ComposerPlugin;
+use debug::DebugPlugin;
+use desk::{DeskPlugin, DESK_COLOR};
+use effects::EffectsPlugin;
+use fonts::{FontsPlugin, ThemedText, UiFont};
+use forgiveness::{ForgivenessPlugin, RoundForgiveness};
+use gamepad::{held_on_gamepad, GamepadPlugin};
+use generator::PatternGenerator;
+use ghost::GhostPlugin;
+use hardware::{
+    uses_hardware_sounds, HardwarePlugin, HARDWARE_GAP_DURATION, HARDWARE_LIT_DURATION,
+    HARDWARE_MAX_LENGTH, HARDWARE_SPEED_UPS, HARDWARE_SUCCESS_DELAY,
+};
+use hint::HintPlugin;
+use hover::HoverPreviewPlugin;
+use input_buffer::InputBufferPlugin;
 use iyes_loopless::prelude::*;
+use letters::ButtonLettersPlugin;
+use loading::LoadingPlugin;
+use menu::MenuPlugin;
+use panning::{PannedSound, PanningPlugin};
+use peek::{PeekPlugin, PeekedThisRound, Peeks, PEEK_KEY};
+use preview::{PatternPreview, PreviewPlugin};
 use rand::{distributions::Standard, prelude::Distribution, Rng};
+use remaining::RemainingPlugin;
+use reverse::ReversePlugin;
+use review::{InputHistory, ReviewPlugin};
+use save::{SavePlugin, Stats};
+use seed::SeedPlugin;
+use serde::{Deserialize, Serialize};
+use settings::{
+    ButtonPalette, DemoMode, FailurePolicy, GameSpeed, Settings, SettingsPlugin, SoundPack,
+};
+use shapes::{ButtonMeshes, ShapesPlugin, CLASSIC_BUTTON_SCALE};
+use share::SharePlugin;
+use toast::{Toast, ToastPlugin};
+use touch::TouchPlugin;
+use transition::TransitionPlugin;
+use turn_timer::TurnTimerPlugin;
+use warmup::{WarmupDone, WarmupPlugin, WARMUP_LENGTH};
+use zen::ZenPlugin;
 
 /// Marker component for the desk/panel thing
 #[derive(Component)]
@@ -24,7 +110,7 @@ struct Desk;
 struct Lamp;
 
 /// Marker component for the buttons
-#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 enum Button {
     Red,
     Green,
@@ -32,6 +118,26 @@ enum Button {
     Yellow,
 }
 
+impl Button {
+    /// Every button
+    const ALL: [Button; 4] = [Button::Red, Button::Green, Button::Blue, Button::Yellow];
+
+    /// Where the button sits on the desk, flipped left to right if `mirrored`
+    fn position(self, mirrored: bool) -> Vec3 {
+        let position = match self {
+            Button::Red => Vec3::new(-0.12, 0.47, -0.12),
+            Button::Green => Vec3::new(-0.12, 0.47, 0.12),
+            Button::Blue => Vec3::new(0.12, 0.47, -0.12),
+            Button::Yellow => Vec3::new(0.12, 0.47, 0.12),
+        };
+        if mirrored {
+            position * Vec3::new(-1.0, 1.0, 1.0)
+        } else {
+            position
+        }
+    }
+}
+
 impl Distribution<Button> for Standard {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Button {
         match rng.gen_range(0..=3) {
@@ -50,18 +156,250 @@ enum ButtonEvent {
     Lit(Button),
 }
 
-/// Stores the button's state and timer
+/// Stores the button's state, its remaining time and how long it started with
 #[derive(Component, Clone, Copy)]
 enum ButtonState {
     Inactive,
-    Pressed { timer: f32 },
-    Lit { timer: f32 },
+    Pressed { timer: f32, duration: f32 },
+    Lit { timer: f32, duration: f32 },
+}
+
+impl ButtonState {
+    /// How brightly the button glows, fading in at the start of its timer
+    /// and out at the end
+    fn brightness(self) -> f32 {
+        match self {
+            ButtonState::Inactive => 0.0,
+            ButtonState::Pressed { timer, duration } | ButtonState::Lit { timer, duration } => {
+                let left = (timer / duration).clamp(0.0, 1.0);
+                ((1.0 - left) / GLOW_FADE_IN)
+                    .min(left / GLOW_FADE_OUT)
+                    .min(1.0)
+            }
+        }
+    }
+
+    /// How long the button has been pressed, if it is
+    fn pressed_for(self) -> Option<f32> {
+        match self {
+            ButtonState::Pressed { timer, duration } => Some(duration - timer),
+            _ => None,
+        }
+    }
+}
+
+/// How long a press shows before another press of the same button restarts it
+const MIN_PRESS_SECONDS: f32 = 0.1;
+/// Most presses that can wait for their turn to show
+const MAX_QUEUED_PRESSES: u32 = 3;
+/// How soon after a press the same button can be pressed again, so input
+/// jitter can't turn one click into two
+const PRESS_COOLDOWN_SECONDS: f32 = 0.1;
+
+/// How far pressed buttons sink into the desk
+const PRESS_DEPTH: f32 = 0.02;
+
+/// Parts of a glow's duration spent fading in and out
+const GLOW_FADE_IN: f32 = 0.1;
+const GLOW_FADE_OUT: f32 = 0.4;
+
+/// How long buttons stay lit or pressed, and the gap between demo buttons
+///
+/// The demo's fixed timestep is `lit_duration + gap_duration`, so the glow and
+/// the dark time between buttons can be tuned separately. Durations should be
+/// read through the methods, which apply the `GameSpeed` and, for the demo and
+/// the turn timer, the accessibility timing, unless the timings are `fixed`.
+#[derive(Resource)]
+struct ButtonTimings {
+    /// How long a clicked button stays down
+    pressed: f32,
+    /// How long a demo button glows
+    lit_duration: f32,
+    /// How long the buttons stay dark before the next demo button
+    gap_duration: f32,
+    // Slower timings for the first round, so new players can catch it
+    intro_lit_duration: f32,
+    intro_gap_duration: f32,
+    intro_length: usize,
+    // Number of rounds to ease from the intro timings to the normal ones
+    intro_ease: usize,
+    /// Breathing room after completing a pattern, before the next demo
+    success_delay: f32,
+    /// Stretches the demo and the turn timer, set by adaptive difficulty
+    pace: f32,
+    /// Pattern lengths the demo speeds up at, with the lit duration from then on
+    speed_ups: &'static [(usize, f32)],
+    /// Ignores the game speed, accessibility timing, demo fast-forward and pace
+    fixed: bool,
+}
+
+impl Default for ButtonTimings {
+    fn default() -> Self {
+        Self {
+            pressed: 0.5,
+            lit_duration: 0.8,
+            gap_duration: 0.2,
+            intro_lit_duration: 1.2,
+            intro_gap_duration: 0.6,
+            intro_length: 2,
+            intro_ease: 2,
+            success_delay: 0.6,
+            pace: 1.0,
+            speed_ups: &[],
+            fixed: false,
+        }
+    }
+}
+
+impl ButtonTimings {
+    /// Slow, relaxed timings for zen mode
+    fn zen() -> Self {
+        Self {
+            lit_duration: 1.2,
+            gap_duration: 0.6,
+            intro_lit_duration: 1.6,
+            intro_gap_duration: 0.8,
+            success_delay: 1.5,
+            ..Default::default()
+        }
+    }
+
+    /// The timings for `game_mode`
+    fn for_mode(game_mode: GameMode) -> Self {
+        match game_mode {
+            GameMode::Zen => Self::zen(),
+            GameMode::Hardware => Self::hardware(),
+            GameMode::Classic | GameMode::Practice => Self::default(),
+        }
+    }
+
+    /// The original Simon's timings, with no intro and a few speed-ups, left
+    /// exactly as they were
+    fn hardware() -> Self {
+        Self {
+            lit_duration: HARDWARE_LIT_DURATION,
+            gap_duration: HARDWARE_GAP_DURATION,
+            intro_lit_duration: HARDWARE_LIT_DURATION,
+            intro_gap_duration: HARDWARE_GAP_DURATION,
+            intro_length: 0,
+            intro_ease: 0,
+            success_delay: HARDWARE_SUCCESS_DELAY,
+            speed_ups: &HARDWARE_SPEED_UPS,
+            fixed: true,
+            ..Default::default()
+        }
+    }
+
+    /// How much of the intro timings to use for a pattern of this length
+    fn intro_blend(&self, pattern_length: usize) -> f32 {
+        if pattern_length <= self.intro_length {
+            1.0
+        } else {
+            let rounds_past = (pattern_length - self.intro_length) as f32;
+            (1.0 - rounds_past / (self.intro_ease + 1) as f32).max(0.0)
+        }
+    }
+
+    /// `seconds` at the game speed
+    fn at_speed(&self, seconds: f32, speed: GameSpeed) -> f32 {
+        if self.fixed {
+            seconds
+        } else {
+            speed.scale(seconds)
+        }
+    }
+
+    /// `seconds` at the game speed, stretched by the accessibility timing and pace
+    fn stretched(&self, seconds: f32, settings: &Settings) -> f32 {
+        if self.fixed {
+            seconds
+        } else {
+            settings.demo_duration(seconds) * self.pace
+        }
+    }
+
+    /// How long a clicked button stays down
+    fn pressed(&self, speed: GameSpeed) -> f32 {
+        self.at_speed(self.pressed, speed)
+    }
+
+    /// How long a demo button stays lit
+    fn lit(&self, pattern_length: usize, settings: &Settings) -> f32 {
+        let blend = self.intro_blend(pattern_length);
+        // The last speed-up reached sets how long buttons stay lit
+        let lit_duration = self
+            .speed_ups
+            .iter()
+            .rev()
+            .find(|(length, _)| pattern_length >= *length)
+            .map_or(self.lit_duration, |(_, lit)| *lit);
+        self.stretched(
+            lit_duration + (self.intro_lit_duration - lit_duration) * blend,
+            settings,
+        )
+    }
+
+    /// How long the gap between demo buttons is
+    fn gap(&self, pattern_length: usize, settings: &Settings) -> f32 {
+        let blend = self.intro_blend(pattern_length);
+        self.stretched(
+            self.gap_duration + (self.intro_gap_duration - self.gap_duration) * blend,
+            settings,
+        )
+    }
+
+    /// How long to wait after a successful round
+    fn success_delay(&self, speed: GameSpeed) -> f32 {
+        self.at_speed(self.success_delay, speed)
+    }
+
+    /// How long the turn timer gives for each press, stretched like the demo
+    fn turn_time(&self, seconds: f32, settings: &Settings) -> f32 {
+        self.stretched(seconds, settings)
+    }
+
+    /// How much faster the demo shows a button, see `Settings::demo_speedup`
+    fn demo_speedup(&self, settings: &Settings, newest: bool) -> f32 {
+        if self.fixed {
+            1.0
+        } else {
+            settings.demo_speedup(newest)
+        }
+    }
 }
 
 /// Stores the button's previous state
 #[derive(Component)]
 struct PreviousButtonState(ButtonState);
 
+/// Presses of the button waiting for the current one to show for long enough
+#[derive(Component, Default)]
+struct QueuedPresses(u32);
+
+/// Time left before the button takes another press
+#[derive(Component)]
+struct PressCooldown(Timer);
+
+impl Default for PressCooldown {
+    fn default() -> Self {
+        let mut timer = Timer::from_seconds(PRESS_COOLDOWN_SECONDS, TimerMode::Once);
+        // Start ready, so the first press counts
+        timer.tick(timer.duration());
+        Self(timer)
+    }
+}
+
+impl PressCooldown {
+    /// Whether a press counts, starting the cooldown if it does
+    fn try_press(&mut self) -> bool {
+        if !self.0.finished() {
+            return false;
+        }
+        self.0.reset();
+        true
+    }
+}
+
 /// `PickableBundle` without the other stuff
 #[derive(Bundle, Default)]
 struct ClickableBundle {
@@ -69,11 +407,34 @@ struct ClickableBundle {
     interaction: Interaction,
     focus_policy: FocusPolicy,
 }
-/// The current state of the game
+/// The screen the app is on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum AppState {
+    Loading,     // Waiting for the sounds and the font
+    Menu,        // The main menu
+    Settings,    // Changing settings
+    Stats,       // Looking at lifetime stats
+    Leaderboard, // Looking at the best runs
+    Playing,     // In a game, see `SimonState`
+    Paused,      // In a game, but paused
+    GameOver,    // Waiting to play again
+    Victory,     // Reached the max pattern length
+    Composer,    // Making up a pattern for the game to play back
+}
+
+/// The phase of the game, only used while `Playing` or `Paused`
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum SimonState {
+    Idle,      // Not in a game
     MonkeySee, // Showing the pattern
     MonkeyDo,  // Waiting for the player
+    Review,    // Showing where the player went wrong
+}
+
+/// Leaves the game for `screen`, ending the current phase
+fn leave_game(commands: &mut Commands, screen: AppState) {
+    commands.insert_resource(NextState(screen));
+    commands.insert_resource(NextState(SimonState::Idle));
 }
 
 /// Event for things that happen in game
@@ -84,30 +445,141 @@ enum SimonEvent {
     Failure,
 }
 
-/// Resource for ending `MonkeyDo`
-// I don't like this :(
+/// Event for pressing the wrong button during `MonkeyDo`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct WrongPress(Button);
+
+/// Whether the run counts towards the high score
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum GameMode {
+    /// Failing ends the run
+    #[default]
+    Classic,
+    /// Failing replays the pattern, and the high score is left alone
+    Practice,
+    /// Like practice, but slower and calmer, without a scoreboard
+    Zen,
+    /// The original Simon's tones, timing and win at 31, without the high score
+    Hardware,
+}
+
+impl GameMode {
+    /// Whether failing replays the pattern instead of ending the run
+    fn replays_on_failure(self, settings: &Settings) -> bool {
+        match self {
+            GameMode::Classic => false,
+            GameMode::Practice | GameMode::Zen => true,
+            GameMode::Hardware => !settings.hardware_strict,
+        }
+    }
+}
+
+/// Event for points being added to the `Score`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct PointsAwarded(usize);
+
+/// Event for passing the old high score during a run, holding the old one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct NewHighScore(usize);
+
+/// Extra lives earned by score, spent to retry a failed pattern
+#[derive(Resource)]
+struct Lives {
+    current: usize,
+    /// Most lives that can be held at once
+    max: usize,
+    /// A life is earned every time the score reaches a multiple of this
+    interval: usize,
+    /// The last score a life was earned at this run, so each is only earned once
+    awarded_at: usize,
+}
+
+impl Default for Lives {
+    fn default() -> Self {
+        Self {
+            current: 0,
+            max: 3,
+            interval: 10,
+            awarded_at: 0,
+        }
+    }
+}
+
+/// Failures in a row this run, so Second Chance only forgives the first
 #[derive(Resource, Default)]
-struct StateSwitch;
+struct ConsecutiveFailures(usize);
+
+/// Label for checking presses against the pattern
+#[derive(SystemLabel)]
+struct ValidatePresses;
+
+/// Resource for waiting a moment after a successful round
+#[derive(Resource)]
+struct AdvanceDelay(Timer);
+
+/// Resource for replaying the pattern without adding to it
+#[derive(Resource)]
+struct RepeatPattern;
 
 /// The pattern to remember
 #[derive(Resource, Default)]
 struct Pattern(Vec<Button>);
 
+impl Pattern {
+    /// The button to press after `progress` correct presses, counting from
+    /// the end once the pattern is long enough to be repeated backwards
+    fn expected(&self, progress: usize, settings: &Settings) -> Option<Button> {
+        if settings.reverses_input(self.0.len()) {
+            self.0.iter().rev().nth(progress).copied()
+        } else {
+            self.0.get(progress).copied()
+        }
+    }
+}
+
+/// The pattern from the last game over, for retrying it
+#[derive(Resource, Default)]
+struct FailedPattern(Vec<Button>);
+
 /// Progress along the pattern
 #[derive(Resource, Default)]
 struct Progress(usize);
 
+/// How much faster the demo button being shown is, from `Settings::demo_speedup`
+#[derive(Resource)]
+struct DemoSpeedup(f32);
+
+impl Default for DemoSpeedup {
+    fn default() -> Self {
+        DemoSpeedup(1.0)
+    }
+}
+
+/// Whether the run started at a higher level, which doesn't count for the high score
+#[derive(Resource, Default)]
+struct BoostedStart(bool);
+
+/// How long the current run has been played, not counting pauses
+#[derive(Resource, Default)]
+struct RunTime(Stopwatch);
+
 /// The score to be displayed
 #[derive(Resource, Default)]
 struct Score {
     current: usize,
     high: usize,
+    /// Whether the high score has been beaten this run, so it's only celebrated once
+    new_best: bool,
 }
 
 /// Marker component for the scoreboard
 #[derive(Component)]
 struct Scoreboard;
 
+/// Marker component for the label saying whether to watch or repeat
+#[derive(Component)]
+struct PhaseLabel;
+
 #[derive(Resource, Default)]
 struct AudioHandles {
     red: Option<Handle<AudioSource>>,
@@ -115,22 +587,66 @@ struct AudioHandles {
     blue: Option<Handle<AudioSource>>,
     yellow: Option<Handle<AudioSource>>,
     incorrect: Option<Handle<AudioSource>>,
+    /// The sound pack the button sounds were loaded from
+    sound_pack: SoundPack,
+}
+
+impl AudioHandles {
+    /// The sound for `button`
+    fn button_mut(&mut self, button: Button) -> &mut Option<Handle<AudioSource>> {
+        match button {
+            Button::Red => &mut self.red,
+            Button::Green => &mut self.green,
+            Button::Blue => &mut self.blue,
+            Button::Yellow => &mut self.yellow,
+        }
+    }
+
+    /// Loads every button sound from `sound_pack`
+    fn load_buttons(&mut self, asset_server: &AssetServer, sound_pack: SoundPack) {
+        self.sound_pack = sound_pack;
+        for button in Button::ALL {
+            *self.button_mut(button) =
+                Some(asset_server.load(button_sound_path(sound_pack, button)));
+        }
+    }
+}
+
+/// Where `button`'s sound is in `sound_pack`
+fn button_sound_path(sound_pack: SoundPack, button: Button) -> String {
+    let name = match button {
+        Button::Red => "red",
+        Button::Green => "green",
+        Button::Blue => "blue",
+        Button::Yellow => "yellow",
+    };
+    format!("{}/{name}.ogg", sound_pack.directory())
 }
 
-// I don't like using strings for identifiers
-const FIXEDUPDATE: &str = "FixedUpdate";
+/// Label for the fixed timestep the demo is shown on
+///
+/// iyes_loopless looks timesteps up by name, so use `FixedUpdate.as_str()`
+/// for that
+#[derive(StageLabel)]
+struct FixedUpdate;
 
 fn main() {
     let mut app = App::new();
     app
-        // Black background
-        .insert_resource(ClearColor(Color::BLACK))
+        // Black background, unless high contrast is on
+        .insert_resource(ClearColor(BACKGROUND_COLOR))
         // Default plugins (useful!)
         .add_plugins(DefaultPlugins)
         // Mouse support
         .add_plugins(DefaultPickingPlugins)
-        // Exit on Esc
-        .add_system(close_on_esc)
+        // Touch support
+        .add_plugin(TouchPlugin)
+        // UI font, with a fallback
+        .add_plugin(FontsPlugin)
+        // Persistent settings
+        .add_plugin(SettingsPlugin)
+        // Persistent high score
+        .add_plugin(SavePlugin)
         // Spawn stuff
         .add_startup_system(setup)
         .add_startup_system(load_assets)
@@ -138,35 +654,163 @@ fn main() {
         .add_event::<ButtonEvent>()
         .init_resource::<AudioHandles>()
         .add_system(button_event_handler)
-        .add_system(button_state_manager)
+        .add_system(button_state_manager.run_not_in_state(AppState::Paused))
+        .add_system(tick_press_cooldowns)
         .add_system(button_controller)
+        .add_system(apply_bloom)
+        .add_system(apply_shadows)
+        .add_system(apply_msaa)
+        .add_system(apply_button_layout)
+        .add_system(apply_scoreboard_corner)
+        .add_system(apply_contrast)
+        .add_system(apply_sound_pack)
         .add_system(play_button_sound)
         .add_system(play_game_sound)
         // Store the pattern as a resource
         .init_resource::<Pattern>()
         .init_resource::<Progress>()
+        .init_resource::<DemoSpeedup>()
+        .init_resource::<FailedPattern>()
+        .init_resource::<ButtonTimings>()
+        .init_resource::<Performance>()
+        .add_system(apply_mode_timings)
+        .add_system(apply_adaptive_difficulty)
+        // Start on the loading screen, then the main menu
+        .add_loopless_state(AppState::Loading)
+        .add_loopless_state(SimonState::Idle)
         // The "Monkey See" state
-        .add_loopless_state(SimonState::MonkeySee)
+        .insert_resource(PatternGenerator::from_args())
         .add_enter_system(SimonState::MonkeySee, update_pattern)
-        .add_fixed_timestep(Duration::from_secs_f32(1.0), FIXEDUPDATE)
+        .add_enter_system(
+            SimonState::MonkeySee,
+            update_demo_timestep.after(update_pattern),
+        )
+        .add_fixed_timestep(Duration::from_secs_f32(1.0), FixedUpdate.as_str())
         .add_fixed_timestep_system(
-            FIXEDUPDATE,
+            FixedUpdate.as_str(),
             0,
-            show_button.run_in_state(SimonState::MonkeySee),
+            show_button
+                .run_in_state(AppState::Playing)
+                .run_in_state(SimonState::MonkeySee),
+        )
+        .add_exit_system(SimonState::MonkeySee, reset_demo_speedup)
+        .add_system(
+            skip_demo
+                .run_in_state(AppState::Playing)
+                .run_in_state(SimonState::MonkeySee),
         )
         // The "Monkey Do" state
         .add_event::<SimonEvent>()
-        .add_system(press_buttons.run_in_state(SimonState::MonkeyDo))
-        .add_system(validate_buttons.run_in_state(SimonState::MonkeyDo))
-        .add_system(game_event_handler.run_in_state(SimonState::MonkeyDo))
-        .add_fixed_timestep_system(
-            FIXEDUPDATE,
-            0,
-            state_switch_event_handler.run_in_state(SimonState::MonkeyDo),
+        .add_event::<WrongPress>()
+        .add_system(
+            press_buttons
+                .run_in_state(AppState::Playing)
+                .run_in_state(SimonState::MonkeyDo)
+                .run_unless_resource_exists::<PatternPreview>(),
+        )
+        .add_exit_system(SimonState::MonkeyDo, end_round)
+        // Presses after the round is over don't count
+        .add_system(
+            validate_buttons
+                .run_in_state(AppState::Playing)
+                .run_in_state(SimonState::MonkeyDo)
+                .run_unless_resource_exists::<AdvanceDelay>()
+                .run_unless_resource_exists::<PatternPreview>()
+                .label(ValidatePresses),
+        )
+        // Runs right after the press is checked, so the round ends before
+        // another press can be
+        .add_system(
+            game_event_handler
+                .run_in_state(AppState::Playing)
+                .run_in_state(SimonState::MonkeyDo)
+                .after(ValidatePresses),
+        )
+        .add_system(check_progress)
+        .add_system(
+            advance_after_delay
+                .run_in_state(AppState::Playing)
+                .run_in_state(SimonState::MonkeyDo),
         )
-        .init_resource::<Score>()
+        .init_resource::<GameMode>()
+        .init_resource::<Lives>()
+        .init_resource::<ConsecutiveFailures>()
+        .add_enter_system(AppState::Menu, reset_consecutive_failures)
+        .add_enter_system(AppState::GameOver, reset_consecutive_failures)
+        .add_event::<PointsAwarded>()
+        .add_event::<NewHighScore>()
         .add_system(update_score)
-        .add_system(update_scoreboard);
+        .init_resource::<RunTime>()
+        .init_resource::<BoostedStart>()
+        .add_system(tick_run_time.run_in_state(AppState::Playing))
+        .add_system(update_scoreboard)
+        .add_system(update_phase_label)
+        // Waits for the sounds and the font before the main menu
+        .add_plugin(LoadingPlugin)
+        // Menus and the game over screen
+        .add_plugin(MenuPlugin)
+        // Particles and celebrations
+        .add_plugin(EffectsPlugin)
+        // Gamepad input and rumble
+        .add_plugin(GamepadPlugin)
+        // Demo game on the main menu
+        .add_plugin(AttractPlugin)
+        // Camera intro
+        .add_plugin(CameraControlPlugin)
+        // Early presses at the end of the demo
+        .add_plugin(InputBufferPlugin)
+        // Button shapes
+        .add_plugin(ShapesPlugin)
+        // Desk themes
+        .add_plugin(DeskPlugin)
+        // Grid behind the desk
+        .add_plugin(BackdropPlugin)
+        // Mistake review before the game over screen
+        .add_plugin(ReviewPlugin)
+        // Zen mode
+        .add_plugin(ZenPlugin)
+        // The original Simon's rules and sounds
+        .add_plugin(HardwarePlugin)
+        // Best run replay
+        .add_plugin(GhostPlugin)
+        // Fades between showing and repeating the pattern
+        .add_plugin(TransitionPlugin)
+        // Stereo button sounds
+        .add_plugin(PanningPlugin)
+        // Pattern preview assist
+        .add_plugin(PreviewPlugin)
+        // Hints for idle players
+        .add_plugin(HintPlugin)
+        // Win streaks and breaking them
+        .add_plugin(ComboPlugin)
+        // Making up patterns for the game to play back
+        .add_plugin(ComposerPlugin)
+        // Notifications
+        .add_plugin(ToastPlugin)
+        // Lighting up the next button a few times a run
+        .add_plugin(PeekPlugin)
+        // Ignoring a few wrong presses each round
+        .add_plugin(ForgivenessPlugin)
+        // Patterns written by the player
+        .add_plugin(ChallengePlugin)
+        // Hearing a button before pressing it
+        .add_plugin(HoverPreviewPlugin)
+        // Time limit for each press
+        .add_plugin(TurnTimerPlugin)
+        // Sharing daily results
+        .add_plugin(SharePlugin)
+        // Warning about reversed input
+        .add_plugin(ReversePlugin)
+        // Unscored round at the start of a run
+        .add_plugin(WarmupPlugin)
+        // Letters on the buttons
+        .add_plugin(ButtonLettersPlugin)
+        // Showing and picking run seeds
+        .add_plugin(SeedPlugin)
+        // Counting down the presses left
+        .add_plugin(RemainingPlugin)
+        // Event logging with --log-events
+        .add_plugin(DebugPlugin);
 
     // Include an inspector if the `inspector` feature is enabled
     #[cfg(feature = "inspector")]
@@ -175,12 +819,26 @@ fn main() {
     app.run();
 }
 
+/// Limits for the desk tilt setting, in degrees
+const MIN_DESK_TILT: f32 = 0.0;
+const MAX_DESK_TILT: f32 = 60.0;
+
+/// Where the desk sits, tilted towards the camera by the desk tilt setting
+fn desk_transform(settings: &Settings) -> Transform {
+    let tilt = settings.desk_tilt.clamp(MIN_DESK_TILT, MAX_DESK_TILT);
+    Transform::from_translation(Vec3::new(0.0, -0.6, -2.0))
+        .with_rotation(Quat::from_rotation_x(tilt.to_radians()))
+}
+
 /// Spawns the camera and panel
 fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    asset_server: Res<AssetServer>,
+    font: Res<UiFont>,
+    palette: Res<ButtonPalette>,
+    button_meshes: Res<ButtonMeshes>,
+    settings: Res<Settings>,
 ) {
     // Camera
     commands
@@ -198,9 +856,8 @@ fn setup(
     commands
         .spawn(PbrBundle {
             mesh: meshes.add(Box::new(2.0, 1.0, 1.0).into()),
-            material: materials.add(Color::ANTIQUE_WHITE.into()),
-            transform: Transform::from_translation(Vec3::new(0.0, -0.6, -2.0))
-                .with_rotation(Quat::from_rotation_x(PI / 6.0)),
+            material: materials.add(DESK_COLOR.into()),
+            transform: desk_transform(&settings),
             ..Default::default()
         })
         .insert(Desk)
@@ -211,7 +868,7 @@ fn setup(
                     spot_light: SpotLight {
                         intensity: 100.0,
                         outer_angle: 0.3,
-                        shadows_enabled: true,
+                        shadows_enabled: !settings.performance_mode,
                         shadow_depth_bias: 0.0,
                         ..Default::default()
                     },
@@ -222,64 +879,30 @@ fn setup(
                 .insert(Lamp);
 
             // Buttons
-            //TODO: Can this be refactored?
-            parent
-                .spawn(PbrBundle {
-                    mesh: meshes.add(Box::new(1.0, 1.0, 1.0).into()),
-                    material: materials.add(Color::RED.into()),
-                    transform: Transform::from_translation(Vec3::new(-0.12, 0.47, -0.12))
-                        .with_scale(Vec3::splat(0.2)),
-                    ..Default::default()
-                })
-                .insert(ClickableBundle::default())
-                .insert(ButtonState::Inactive)
-                .insert(PreviousButtonState(ButtonState::Inactive))
-                .insert(Button::Red);
-
-            parent
-                .spawn(PbrBundle {
-                    mesh: meshes.add(Box::new(1.0, 1.0, 1.0).into()),
-                    material: materials.add(Color::GREEN.into()),
-                    transform: Transform::from_translation(Vec3::new(-0.12, 0.47, 0.12))
-                        .with_scale(Vec3::splat(0.2)),
-                    ..Default::default()
-                })
-                .insert(ClickableBundle::default())
-                .insert(ButtonState::Inactive)
-                .insert(PreviousButtonState(ButtonState::Inactive))
-                .insert(Button::Green);
-
-            parent
-                .spawn(PbrBundle {
-                    mesh: meshes.add(Box::new(1.0, 1.0, 1.0).into()),
-                    material: materials.add(Color::BLUE.into()),
-                    transform: Transform::from_translation(Vec3::new(0.12, 0.47, -0.12))
-                        .with_scale(Vec3::splat(0.2)),
-                    ..Default::default()
-                })
-                .insert(ClickableBundle::default())
-                .insert(ButtonState::Inactive)
-                .insert(PreviousButtonState(ButtonState::Inactive))
-                .insert(Button::Blue);
-
-            parent
-                .spawn(PbrBundle {
-                    mesh: meshes.add(Box::new(1.0, 1.0, 1.0).into()),
-                    material: materials.add(Color::YELLOW.into()),
-                    transform: Transform::from_translation(Vec3::new(0.12, 0.47, 0.12))
-                        .with_scale(Vec3::splat(0.2)),
-                    ..Default::default()
-                })
-                .insert(ClickableBundle::default())
-                .insert(ButtonState::Inactive)
-                .insert(PreviousButtonState(ButtonState::Inactive))
-                .insert(Button::Yellow);
+            for button in Button::ALL {
+                parent
+                    .spawn(PbrBundle {
+                        mesh: button_meshes.classic.clone(),
+                        material: materials.add(palette.color(button).into()),
+                        transform: Transform::from_translation(
+                            button.position(settings.mirror_layout),
+                        )
+                        .with_scale(CLASSIC_BUTTON_SCALE),
+                        ..Default::default()
+                    })
+                    .insert(ClickableBundle::default())
+                    .insert(ButtonState::Inactive)
+                    .insert(PreviousButtonState(ButtonState::Inactive))
+                    .insert(QueuedPresses::default())
+                    .insert(PressCooldown::default())
+                    .insert(button);
+            }
         });
 
     let score_textstyle = TextStyle {
-        font: asset_server.load("fonts/comic.ttf"),
+        font: font.handle.clone(),
         font_size: 36.0,
-        color: Color::WHITE,
+        color: settings.ui_text_color.color(),
     };
 
     commands
@@ -299,73 +922,150 @@ fn setup(
                 },
                 TextSection {
                     value: "0".into(),
+                    style: score_textstyle.clone(),
+                },
+                TextSection {
+                    value: "".into(),
+                    style: score_textstyle.clone(),
+                },
+                TextSection {
+                    value: "".into(),
+                    style: score_textstyle.clone(),
+                },
+                TextSection {
+                    value: "".into(),
+                    style: score_textstyle.clone(),
+                },
+                TextSection {
+                    value: "".into(),
                     style: score_textstyle,
                 },
             ])
             .with_alignment(TextAlignment::CENTER_RIGHT),
             style: Style {
-                margin: UiRect {
-                    left: Val::Percent(2.0),
-                    right: Val::Undefined,
-                    top: Val::Undefined,
-                    bottom: Val::Percent(2.0),
+                position_type: PositionType::Absolute,
+                position: settings.scoreboard_corner.position(),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert((Scoreboard, ThemedText));
+
+    commands
+        .spawn(TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: font.handle.clone(),
+                    font_size: 48.0,
+                    color: settings.ui_text_color.color(),
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Percent(2.0),
+                    right: Val::Percent(2.0),
+                    ..Default::default()
                 },
                 ..Default::default()
             },
+            visibility: Visibility::INVISIBLE,
             ..Default::default()
         })
-        .insert(Scoreboard);
+        .insert((PhaseLabel, ThemedText));
 }
 
 /// Handles `ButtonEvent`s and sets `ButtonState`s
+///
+/// A press that comes in before the last one has shown for `MIN_PRESS_SECONDS`
+/// is queued for `button_state_manager`, so quick presses each get seen
 fn button_event_handler(
     mut event_reader: EventReader<ButtonEvent>,
-    mut buttons: Query<(&Button, &mut ButtonState, &mut PreviousButtonState)>,
+    mut buttons: Query<(
+        &Button,
+        &mut ButtonState,
+        &mut PreviousButtonState,
+        &mut QueuedPresses,
+    )>,
+    timings: Res<ButtonTimings>,
+    pattern: Res<Pattern>,
+    settings: Res<Settings>,
+    speedup: Res<DemoSpeedup>,
 ) {
     for event in event_reader.iter() {
         match event {
             ButtonEvent::Pressed(button) => {
-                for (_, mut state, mut previous) in
-                    buttons.iter_mut().filter(|(b, _, _)| *b == button)
+                for (_, mut state, mut previous, mut queued) in
+                    buttons.iter_mut().filter(|(b, ..)| *b == button)
                 {
+                    if state
+                        .pressed_for()
+                        .is_some_and(|shown| shown < MIN_PRESS_SECONDS)
+                    {
+                        queued.0 = (queued.0 + 1).min(MAX_QUEUED_PRESSES);
+                        continue;
+                    }
                     *previous = PreviousButtonState(*state);
-                    *state = ButtonState::Pressed { timer: 0.5 };
+                    let duration = timings.pressed(settings.game_speed);
+                    *state = ButtonState::Pressed {
+                        timer: duration,
+                        duration,
+                    };
                 }
             }
             ButtonEvent::Lit(button) => {
-                for (_, mut state, mut previous) in
-                    buttons.iter_mut().filter(|(b, _, _)| *b == button)
+                for (_, mut state, mut previous, mut queued) in
+                    buttons.iter_mut().filter(|(b, ..)| *b == button)
                 {
+                    queued.0 = 0;
                     *previous = PreviousButtonState(*state);
-                    *state = ButtonState::Lit { timer: 0.8 };
+                    let duration = timings.lit(pattern.0.len(), &settings) / speedup.0;
+                    *state = ButtonState::Lit {
+                        timer: duration,
+                        duration,
+                    };
                 }
             }
         }
     }
 }
 
-/// Manages `ButtonState`s and their timers
+/// Manages `ButtonState`s and their timers, restarting presses for queued ones
 fn button_state_manager(
-    mut buttons: Query<(&mut ButtonState, &mut PreviousButtonState)>,
+    mut buttons: Query<(
+        &mut ButtonState,
+        &mut PreviousButtonState,
+        &mut QueuedPresses,
+    )>,
     time: Res<Time>,
 ) {
-    for (mut state, mut previous) in buttons.iter_mut() {
+    for (mut state, mut previous, mut queued) in buttons.iter_mut() {
         match *state {
             ButtonState::Inactive => {}
-            ButtonState::Pressed { timer } => {
-                if timer > 0.0 {
+            ButtonState::Pressed { timer, duration } => {
+                if queued.0 > 0 && duration - timer >= MIN_PRESS_SECONDS {
+                    queued.0 -= 1;
+                    *previous = PreviousButtonState(*state);
+                    *state = ButtonState::Pressed {
+                        timer: duration,
+                        duration,
+                    };
+                } else if timer > 0.0 {
                     *state = ButtonState::Pressed {
                         timer: timer - time.delta_seconds(),
+                        duration,
                     }
                 } else {
                     *previous = PreviousButtonState(*state);
                     *state = ButtonState::Inactive;
                 }
             }
-            ButtonState::Lit { timer } => {
+            ButtonState::Lit { timer, duration } => {
                 if timer > 0.0 {
                     *state = ButtonState::Lit {
                         timer: timer - time.delta_seconds(),
+                        duration,
                     }
                 } else {
                     *previous = PreviousButtonState(*state);
@@ -379,53 +1079,217 @@ fn button_state_manager(
 /// Animates the buttons
 fn button_controller(
     mut buttons: Query<(
+        &Button,
         &ButtonState,
         &mut PreviousButtonState,
         &mut Transform,
         &Handle<StandardMaterial>,
     )>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    settings: Res<Settings>,
+    palette: Res<ButtonPalette>,
 ) {
-    for (state, mut previous, mut transform, material_handle) in buttons.iter_mut() {
-        let material = materials.get_mut(material_handle).unwrap();
-
+    for (button, state, mut previous, mut transform, material_handle) in buttons.iter_mut() {
         if mem::discriminant(&previous.0) != mem::discriminant(state) {
-            match *state {
-                ButtonState::Inactive => {
-                    material.emissive = Color::BLACK;
-                    if matches!(previous.0, ButtonState::Pressed { .. }) {
-                        transform.translation.y += 0.02;
-                    }
-                    *previous = PreviousButtonState(*state);
-                }
-                ButtonState::Pressed { .. } => {
-                    material.emissive = material.base_color;
-                    transform.translation.y -= 0.02;
-                    *previous = PreviousButtonState(*state);
-                }
-                ButtonState::Lit { .. } => {
-                    material.emissive = material.base_color;
-                    *previous = PreviousButtonState(*state);
-                }
+            *previous = PreviousButtonState(*state);
+        }
+
+        // Pressed buttons sink into the desk, unless motion is reduced
+        let pushed = matches!(state, ButtonState::Pressed { .. }) && !settings.reduced_motion;
+        let y = button.position(settings.mirror_layout).y - if pushed { PRESS_DEPTH } else { 0.0 };
+        if transform.translation.y != y {
+            transform.translation.y = y;
+        }
+
+        // Fade the glow every frame, only touching the material when it changes
+        let brightness = if settings.reduced_motion {
+            1.0
+        } else {
+            state.brightness()
+        };
+        let emissive = match state {
+            ButtonState::Inactive => Color::BLACK,
+            _ => palette.color(*button) * settings.glow() * brightness,
+        };
+        if materials.get(material_handle).unwrap().emissive != emissive {
+            materials.get_mut(material_handle).unwrap().emissive = emissive;
+        }
+    }
+}
+
+/// Adds or removes the camera's bloom when the setting changes
+fn apply_bloom(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    cameras: Query<Entity, With<Camera3d>>,
+) {
+    if settings.is_changed() {
+        for camera in cameras.iter() {
+            if settings.bloom && !settings.performance_mode {
+                commands.entity(camera).insert(BloomSettings::default());
+            } else {
+                commands.entity(camera).remove::<BloomSettings>();
             }
         }
     }
 }
 
-/// Adds a random button to the pattern
-fn update_pattern(mut pattern: ResMut<Pattern>) {
-    let button: Button = rand::random();
-    pattern.0.push(button);
+/// Turns the lamp's shadows off in performance mode
+fn apply_shadows(settings: Res<Settings>, mut lamps: Query<&mut SpotLight, With<Lamp>>) {
+    if !settings.is_changed() {
+        return;
+    }
+    for mut spot_light in lamps.iter_mut() {
+        if spot_light.shadows_enabled == settings.performance_mode {
+            spot_light.shadows_enabled = !settings.performance_mode;
+        }
+    }
+}
+
+/// Sets the anti-aliasing when the setting changes
+fn apply_msaa(settings: Res<Settings>, mut msaa: ResMut<Msaa>) {
+    if settings.is_changed() && msaa.samples != settings.msaa() {
+        msaa.samples = settings.msaa();
+    }
+}
+
+/// Moves the buttons when the mirror layout setting changes
+///
+/// Only the sides are set, `button_controller` handles the height
+fn apply_button_layout(settings: Res<Settings>, mut buttons: Query<(&Button, &mut Transform)>) {
+    if !settings.is_changed() {
+        return;
+    }
+    for (button, mut transform) in buttons.iter_mut() {
+        let position = button.position(settings.mirror_layout);
+        if transform.translation.x != position.x {
+            transform.translation.x = position.x;
+        }
+    }
+}
+
+/// Moves the scoreboard when its corner setting changes
+fn apply_scoreboard_corner(
+    settings: Res<Settings>,
+    mut scoreboards: Query<&mut Style, With<Scoreboard>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    let position = settings.scoreboard_corner.position();
+    for mut style in scoreboards.iter_mut() {
+        if style.position != position {
+            style.position = position;
+        }
+    }
+}
+
+/// Background colors for normal and high contrast
+const BACKGROUND_COLOR: Color = Color::BLACK;
+const HIGH_CONTRAST_BACKGROUND: Color = Color::rgb(0.0, 0.05, 0.25);
+
+/// Swaps the background color when the high contrast setting changes
+///
+/// The desk is handled by `DeskPlugin`
+fn apply_contrast(settings: Res<Settings>, mut clear_color: ResMut<ClearColor>) {
+    if !settings.is_changed() {
+        return;
+    }
+    clear_color.0 = if settings.high_contrast {
+        HIGH_CONTRAST_BACKGROUND
+    } else {
+        BACKGROUND_COLOR
+    };
+}
+
+/// Adds a button from the `PatternGenerator` to the pattern, or enough to
+/// reach the starting level if it's empty
+///
+/// With `DemoMode::NewOnly` the demo starts at the new button. Repeated patterns
+/// have no new button, so they're always shown in full.
+///
+/// A warm-up gets its own random pattern, thrown away once it's repeated
+#[allow(clippy::too_many_arguments)]
+fn update_pattern(
+    mut commands: Commands,
+    mut pattern: ResMut<Pattern>,
+    mut progress: ResMut<Progress>,
+    mut generator: ResMut<PatternGenerator>,
+    mut warmup_done: ResMut<WarmupDone>,
+    repeat: Option<Res<RepeatPattern>>,
+    settings: Res<Settings>,
+) {
+    if repeat.is_some() {
+        commands.remove_resource::<RepeatPattern>();
+        return;
+    }
+    if !warmup_done.0 {
+        if pattern.0.is_empty() {
+            // Kept away from the generator, so seeded runs stay the same
+            pattern.0 = (0..WARMUP_LENGTH).map(|_| rand::random()).collect();
+            return;
+        }
+        warmup_done.0 = true;
+        pattern.0.clear();
+    }
+    let length = if pattern.0.is_empty() {
+        settings.starting_level.max(1)
+    } else {
+        pattern.0.len() + 1
+    };
+    while pattern.0.len() < length {
+        let button = if settings.no_repeats {
+            generator.0.next_without_repeat(&pattern.0)
+        } else {
+            generator.0.next(&pattern.0)
+        };
+        pattern.0.push(button);
+    }
+    if settings.demo_mode == DemoMode::NewOnly {
+        progress.0 = pattern.0.len() - 1;
+    }
+}
+
+/// Paces the demo using the `ButtonTimings` for the current round
+///
+/// Each step lights one button, which glows for `lit` and then stays dark for `gap`
+fn update_demo_timestep(
+    timings: Res<ButtonTimings>,
+    pattern: Res<Pattern>,
+    settings: Res<Settings>,
+    mut timesteps: ResMut<FixedTimesteps>,
+) {
+    let length = pattern.0.len();
+    if let Some(timestep) = timesteps.get_mut(FixedUpdate.as_str()) {
+        timestep.step = Duration::from_secs_f32(
+            timings.lit(length, &settings) + timings.gap(length, &settings),
+        );
+    }
 }
 
 /// Shows the next button in the pattern or ends the "Monkey See" state
+///
+/// Buttons seen in earlier rounds are fast-forwarded if enabled, so the step
+/// after each one is shortened to match
+#[allow(clippy::too_many_arguments)]
 fn show_button(
     mut commands: Commands,
     mut progress: ResMut<Progress>,
     pattern: Res<Pattern>,
     mut button_event_writer: EventWriter<ButtonEvent>,
+    mut speedup: ResMut<DemoSpeedup>,
+    mut timesteps: ResMut<FixedTimesteps>,
+    timings: Res<ButtonTimings>,
+    settings: Res<Settings>,
 ) {
     if let Some(button) = pattern.0.get(progress.0) {
+        let length = pattern.0.len();
+        speedup.0 = timings.demo_speedup(&settings, progress.0 + 1 == length);
+        if let Some(timestep) = timesteps.get_mut(FixedUpdate.as_str()) {
+            timestep.step = Duration::from_secs_f32(
+                (timings.lit(length, &settings) + timings.gap(length, &settings)) / speedup.0,
+            );
+        }
         button_event_writer.send(ButtonEvent::Lit(*button));
         progress.0 += 1;
     } else {
@@ -434,114 +1298,535 @@ fn show_button(
     }
 }
 
+/// Shows buttons lit outside the demo at normal speed
+fn reset_demo_speedup(mut speedup: ResMut<DemoSpeedup>) {
+    speedup.0 = 1.0;
+}
+
+/// Skips the rest of the demo when Space is pressed, if enabled
+///
+/// `show_button` sees the progress past the end and starts `MonkeyDo` on its next step
+fn skip_demo(
+    keys: Res<Input<KeyCode>>,
+    settings: Res<Settings>,
+    pattern: Res<Pattern>,
+    mut progress: ResMut<Progress>,
+) {
+    if settings.skip_demo && keys.just_pressed(KeyCode::Space) {
+        progress.0 = pattern.0.len();
+    }
+}
+
 /// Fires button events when buttons are clicked
+///
+/// If presses trigger on release, clicking only pushes the button down, and the
+/// press counts once it's let go over the button. Either way a button held down,
+/// with the mouse, a finger or a gamepad, presses once until it's let go
+#[allow(clippy::too_many_arguments)]
 fn press_buttons(
-    interactions: Query<(&Interaction, &Button), Changed<Interaction>>,
+    mut interactions: Query<
+        (
+            Entity,
+            &Interaction,
+            &Button,
+            &mut ButtonState,
+            &mut PressCooldown,
+        ),
+        Changed<Interaction>,
+    >,
     mut button_event_writer: EventWriter<ButtonEvent>,
+    settings: Res<Settings>,
+    timings: Res<ButtonTimings>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut held: Local<Vec<Entity>>,
 ) {
-    for (interaction, button) in interactions.iter() {
+    for (entity, interaction, button, mut state, mut cooldown) in interactions.iter_mut() {
+        let on_gamepad = held_on_gamepad(&gamepads, &gamepad_buttons, *button);
+        let index = held.iter().position(|held| *held == entity);
         if *interaction == Interaction::Clicked {
-            button_event_writer.send(ButtonEvent::Pressed(*button));
+            // Still held from before, so it's already been pressed
+            if index.is_some() {
+                continue;
+            }
+            held.push(entity);
+            if !settings.press_on_release {
+                if !on_gamepad && cooldown.try_press() {
+                    button_event_writer.send(ButtonEvent::Pressed(*button));
+                }
+                continue;
+            }
+            let duration = timings.pressed(settings.game_speed);
+            *state = ButtonState::Pressed {
+                timer: duration,
+                duration,
+            };
+        } else if let Some(index) = index {
+            held.swap_remove(index);
+            // Letting go off the button cancels the press
+            if settings.press_on_release
+                && *interaction == Interaction::Hovered
+                && !on_gamepad
+                && cooldown.try_press()
+            {
+                button_event_writer.send(ButtonEvent::Pressed(*button));
+            }
         }
     }
 }
 
+/// Counts down each button's `PressCooldown`
+fn tick_press_cooldowns(mut cooldowns: Query<&mut PressCooldown>, time: Res<Time>) {
+    for mut cooldown in cooldowns.iter_mut() {
+        cooldown.0.tick(time.delta());
+    }
+}
+
 /// Handles button events during `MonkeyDo`
+#[allow(clippy::too_many_arguments)]
 fn validate_buttons(
     mut event_writer: EventWriter<SimonEvent>,
+    mut wrong_press_writer: EventWriter<WrongPress>,
     mut event_reader: EventReader<ButtonEvent>,
     pattern: Res<Pattern>,
     progress: Res<Progress>,
+    mut stats: ResMut<Stats>,
+    mut history: ResMut<InputHistory>,
+    mut forgiveness: ResMut<RoundForgiveness>,
+    settings: Res<Settings>,
 ) {
-    for event in event_reader.iter() {
-        if let ButtonEvent::Pressed(button) = event {
-            if *button == pattern.0[progress.0] {
-                if progress.0 == pattern.0.len() - 1 {
-                    event_writer.send(SimonEvent::Success);
-                } else {
-                    event_writer.send(SimonEvent::Next);
-                }
+    // Every press in a frame would be checked against the same progress,
+    // so only the first one counts
+    let first_press = event_reader.iter().find_map(|event| match event {
+        ButtonEvent::Pressed(button) => Some(*button),
+        ButtonEvent::Lit(_) => None,
+    });
+    event_reader.clear();
+
+    if let Some(button) = first_press {
+        // `check_progress` catches this in debug builds
+        let Some(expected) = pattern.expected(progress.0, &settings) else {
+            error!(
+                "Ignoring a press with nothing left to repeat, progress {} of {}",
+                progress.0,
+                pattern.0.len()
+            );
+            return;
+        };
+        stats.buttons_pressed += 1;
+        // Forgiven presses are shaken off and left out of the review
+        if button != expected && forgiveness.try_forgive() {
+            wrong_press_writer.send(WrongPress(button));
+            return;
+        }
+        history.0.push(button);
+        if button == expected {
+            if progress.0 + 1 == pattern.0.len() {
+                event_writer.send(SimonEvent::Success);
             } else {
-                event_writer.send(SimonEvent::Failure);
+                event_writer.send(SimonEvent::Next);
             }
+        } else {
+            event_writer.send(SimonEvent::Failure);
+            wrong_press_writer.send(WrongPress(button));
         }
     }
 }
 
+/// Makes sure the progress never runs past the end of the pattern, panicking
+/// in debug builds and logging an error otherwise
+fn check_progress(pattern: Res<Pattern>, progress: Res<Progress>) {
+    if !pattern.is_changed() && !progress.is_changed() {
+        return;
+    }
+    let in_bounds = progress.0 <= pattern.0.len();
+    if !in_bounds {
+        error!(
+            "Progress {} is past the end of the pattern of length {}",
+            progress.0,
+            pattern.0.len()
+        );
+    }
+    debug_assert!(in_bounds, "progress ran past the end of the pattern");
+}
+
 /// Sets the progress based on game events
+#[allow(clippy::too_many_arguments)]
 fn game_event_handler(
     mut commands: Commands,
     mut event_reader: EventReader<SimonEvent>,
     mut progress: ResMut<Progress>,
     mut pattern: ResMut<Pattern>,
+    mut failed_pattern: ResMut<FailedPattern>,
+    game_mode: Res<GameMode>,
+    mut stats: ResMut<Stats>,
+    mut lives: ResMut<Lives>,
+    mut consecutive_failures: ResMut<ConsecutiveFailures>,
+    mut toast_writer: EventWriter<Toast>,
+    warmup_done: Res<WarmupDone>,
+    mut performance: ResMut<Performance>,
+    mut timings: ResMut<ButtonTimings>,
+    settings: Res<Settings>,
+    challenge: Option<Res<Challenge>>,
 ) {
+    // Finishing a challenge wins too, as does reaching the original's longest pattern
+    let max_length = challenge
+        .map(|challenge| challenge.0.len())
+        .or((*game_mode == GameMode::Hardware).then_some(HARDWARE_MAX_LENGTH))
+        .or(settings.max_length);
+    // Classic hardware plays by the original's rules, so second chances, lives,
+    // the failure policy and adaptive difficulty don't apply
+    let hardware = *game_mode == GameMode::Hardware;
     for event in event_reader.iter() {
+        // Only the first failure in a row is forgiven, modes that always
+        // replay don't need it
+        let second_chance = settings.second_chance
+            && consecutive_failures.0 == 0
+            && !hardware
+            && !game_mode.replays_on_failure(&settings);
         match event {
+            _ if !warmup_done.0 => {}
+            SimonEvent::Success => consecutive_failures.0 = 0,
+            SimonEvent::Failure => consecutive_failures.0 += 1,
+            SimonEvent::Next => {}
+        }
+        // Each round that ends tunes the pace of the next
+        if settings.adaptive_difficulty && !hardware && warmup_done.0 && *event != SimonEvent::Next
+        {
+            performance.record(*event == SimonEvent::Success);
+            timings.pace = performance.adjust(timings.pace);
+        }
+        match event {
+            // The warm-up is over once it's repeated, `update_pattern` starts the run proper
+            SimonEvent::Success if !warmup_done.0 => {
+                progress.0 = 0;
+                commands.insert_resource(AdvanceDelay(Timer::from_seconds(
+                    timings.success_delay(settings.game_speed),
+                    TimerMode::Once,
+                )));
+            }
+            // Mistakes in the warm-up don't cost anything
+            SimonEvent::Failure if !warmup_done.0 => {
+                progress.0 = 0;
+                commands.insert_resource(RepeatPattern);
+                commands.insert_resource(NextState(SimonState::MonkeySee));
+            }
+            SimonEvent::Success
+                if max_length.is_some_and(|max_length| pattern.0.len() >= max_length) =>
+            {
+                stats.games_played += 1;
+                progress.0 = 0;
+                leave_game(&mut commands, AppState::Victory);
+            }
             SimonEvent::Success => {
                 progress.0 = 0;
-                commands.insert_resource(StateSwitch);
+                commands.insert_resource(AdvanceDelay(Timer::from_seconds(
+                    timings.success_delay(settings.game_speed),
+                    TimerMode::Once,
+                )));
             }
             SimonEvent::Next => {
                 progress.0 += 1;
             }
+            SimonEvent::Failure if second_chance => {
+                // Replay the same pattern without touching the score or lives
+                stats.failures += 1;
+                progress.0 = 0;
+                commands.insert_resource(RepeatPattern);
+                commands.insert_resource(NextState(SimonState::MonkeySee));
+                toast_writer.send(Toast::new("Second chance!"));
+            }
+            SimonEvent::Failure
+                if game_mode.replays_on_failure(&settings) || (!hardware && lives.current > 0) =>
+            {
+                // Try the same pattern again, spending a life outside of practice
+                if *game_mode == GameMode::Classic {
+                    lives.current -= 1;
+                }
+                stats.failures += 1;
+                progress.0 = 0;
+                commands.insert_resource(RepeatPattern);
+                commands.insert_resource(NextState(SimonState::MonkeySee));
+            }
+            SimonEvent::Failure
+                if !hardware && settings.failure_policy != FailurePolicy::ClearPattern =>
+            {
+                stats.failures += 1;
+                progress.0 = 0;
+                if settings.failure_policy == FailurePolicy::ShrinkByOne {
+                    pattern.0.pop();
+                }
+                // An empty pattern needs a new button before it can be shown
+                if !pattern.0.is_empty() {
+                    commands.insert_resource(RepeatPattern);
+                }
+                commands.insert_resource(NextState(SimonState::MonkeySee));
+            }
             SimonEvent::Failure => {
+                // The pattern is kept until the player picks an option on the game over screen
+                stats.failures += 1;
+                stats.games_played += 1;
+                failed_pattern.0 = pattern.0.clone();
                 progress.0 = 0;
-                pattern.0 = Vec::new();
-                commands.insert_resource(StateSwitch);
+                commands.insert_resource(NextState(SimonState::Review));
             }
         }
     }
 }
 
-/// Periodically checks to change to MonkeySee
-fn state_switch_event_handler(mut commands: Commands, state_switch: Option<Res<StateSwitch>>) {
-    if state_switch.is_some() {
-        commands.remove_resource::<StateSwitch>();
-        commands.insert_resource(NextState(SimonState::MonkeySee));
+/// Switches to the timings for the game mode when it changes
+fn apply_mode_timings(game_mode: Res<GameMode>, mut timings: ResMut<ButtonTimings>) {
+    if game_mode.is_changed() {
+        // Adaptive difficulty's pace carries over between modes
+        let pace = timings.pace;
+        *timings = ButtonTimings::for_mode(*game_mode);
+        timings.pace = pace;
+    }
+}
+
+/// Goes back to the normal pace when adaptive difficulty is turned off
+fn apply_adaptive_difficulty(settings: Res<Settings>, mut timings: ResMut<ButtonTimings>) {
+    if settings.is_changed() && !settings.adaptive_difficulty && timings.pace != 1.0 {
+        timings.pace = 1.0;
+    }
+}
+
+/// Gives the next run its second chance back
+fn reset_consecutive_failures(mut consecutive_failures: ResMut<ConsecutiveFailures>) {
+    consecutive_failures.0 = 0;
+}
+
+/// Clears anything left over from the round, in case it was left early
+fn end_round(mut commands: Commands) {
+    commands.remove_resource::<AdvanceDelay>();
+}
+
+/// Starts the next round once the `AdvanceDelay` is over
+fn advance_after_delay(
+    mut commands: Commands,
+    delay: Option<ResMut<AdvanceDelay>>,
+    time: Res<Time>,
+) {
+    if let Some(mut delay) = delay {
+        if delay.0.tick(time.delta()).finished() {
+            commands.remove_resource::<AdvanceDelay>();
+            commands.insert_resource(NextState(SimonState::MonkeySee));
+        }
+    }
+}
+
+/// Times the run while a round is being played
+///
+/// The warm-up isn't part of the run, so it isn't timed
+fn tick_run_time(
+    mut run_time: ResMut<RunTime>,
+    state: Res<CurrentState<SimonState>>,
+    warmup_done: Res<WarmupDone>,
+    time: Res<Time>,
+) {
+    if warmup_done.0 && matches!(state.0, SimonState::MonkeySee | SimonState::MonkeyDo) {
+        run_time.0.tick(time.delta());
     }
 }
 
 /// Update the `Score` based on game events
-fn update_score(mut event_reader: EventReader<SimonEvent>, mut score: ResMut<Score>) {
+#[allow(clippy::too_many_arguments)]
+fn update_score(
+    mut event_reader: EventReader<SimonEvent>,
+    mut points_writer: EventWriter<PointsAwarded>,
+    mut high_score_writer: EventWriter<NewHighScore>,
+    mut toast_writer: EventWriter<Toast>,
+    mut score: ResMut<Score>,
+    mut lives: ResMut<Lives>,
+    game_mode: Res<GameMode>,
+    pattern: Res<Pattern>,
+    mut stats: ResMut<Stats>,
+    boosted_start: Res<BoostedStart>,
+    settings: Res<Settings>,
+    peeked: Res<PeekedThisRound>,
+    warmup_done: Res<WarmupDone>,
+    challenge: Option<Res<Challenge>>,
+) {
     for event in event_reader.iter() {
         match event {
+            // `update_pattern` only ends the warm-up when the next round starts,
+            // so it's still going when its success is read
+            SimonEvent::Success if !warmup_done.0 => {}
             SimonEvent::Success => {
-                score.current += 1;
-                if score.current > score.high {
+                // Peeking costs the round its point
+                let points = if peeked.0 { 0 } else { 1 };
+                score.current += points;
+                points_writer.send(PointsAwarded(points));
+                if pattern.0.len() > stats.longest_pattern {
+                    stats.longest_pattern = pattern.0.len();
+                }
+                let ranked =
+                    !boosted_start.0 && !settings.preview_unranked() && challenge.is_none();
+                if *game_mode == GameMode::Classic && ranked && score.current > score.high {
+                    // Only the first time the old high score is passed, and not on a first run
+                    if score.high > 0 && !score.new_best {
+                        high_score_writer.send(NewHighScore(score.high));
+                    }
+                    score.new_best = true;
                     score.high = score.current;
                 }
+
+                // Continues can take the score back under a milestone, so only
+                // milestones past the last one count
+                let milestone = score.current / lives.interval * lives.interval;
+                if *game_mode == GameMode::Classic && milestone > lives.awarded_at {
+                    lives.awarded_at = milestone;
+                    if lives.current < lives.max {
+                        lives.current += 1;
+                        toast_writer.send(Toast::new("Extra life!"));
+                    }
+                }
             }
-            SimonEvent::Failure => score.current = 0,
-            SimonEvent::Next => {}
+            // The score is reset when starting a new run from the game over screen
+            SimonEvent::Failure | SimonEvent::Next => {}
         }
     }
 }
 
 /// Update the `Scoreboard`'s text
-fn update_scoreboard(score: Res<Score>, mut score_text_query: Query<&mut Text, With<Scoreboard>>) {
-    if score.is_changed() {
-        for mut score_text in score_text_query.iter_mut() {
+#[allow(clippy::too_many_arguments)]
+fn update_scoreboard(
+    score: Res<Score>,
+    game_mode: Res<GameMode>,
+    lives: Res<Lives>,
+    boosted_start: Res<BoostedStart>,
+    settings: Res<Settings>,
+    peeks: Res<Peeks>,
+    forgiveness: Res<RoundForgiveness>,
+    challenge: Option<Res<Challenge>>,
+    mut score_text_query: Query<(&mut Text, &mut Visibility), With<Scoreboard>>,
+) {
+    if score.is_changed()
+        || game_mode.is_changed()
+        || lives.is_changed()
+        || peeks.is_changed()
+        || forgiveness.is_changed()
+        || challenge
+            .as_ref()
+            .is_some_and(|challenge| challenge.is_changed())
+        || boosted_start.is_changed()
+        || settings.is_changed()
+    {
+        for (mut score_text, mut visibility) in score_text_query.iter_mut() {
+            // No scoring pressure in zen mode
+            visibility.is_visible = *game_mode != GameMode::Zen;
             score_text.sections[1].value = score.current.to_string();
             score_text.sections[3].value = score.high.to_string();
+            score_text.sections[4].value = match *game_mode {
+                GameMode::Classic if challenge.is_some() => "\nChallenge (no high score)".into(),
+                GameMode::Classic if boosted_start.0 => "\nHead start (no high score)".into(),
+                GameMode::Classic if settings.preview_unranked() => {
+                    "\nPreview assist (no high score)".into()
+                }
+                GameMode::Classic | GameMode::Zen => String::new(),
+                GameMode::Practice => "\nPractice (not scored)".into(),
+                GameMode::Hardware => "\nClassic hardware (no high score)".into(),
+            };
+            score_text.sections[5].value = match lives.current {
+                0 => String::new(),
+                lives => format!("\nLives: {lives}"),
+            };
+            score_text.sections[6].value = match peeks.0 {
+                0 => String::new(),
+                peeks => format!("\nPeeks: {peeks} ({PEEK_KEY:?})"),
+            };
+            score_text.sections[7].value = match settings.forgiveness {
+                0 => String::new(),
+                allowed => format!("\nForgiveness: {}/{allowed}", forgiveness.0),
+            };
+        }
+    }
+}
+
+/// Shows "Watch" while the pattern is shown and "Repeat" while the player
+/// repeats it, hiding the `PhaseLabel` otherwise
+fn update_phase_label(
+    state: Res<CurrentState<SimonState>>,
+    warmup_done: Res<WarmupDone>,
+    mut labels: Query<(&mut Text, &mut Visibility), With<PhaseLabel>>,
+) {
+    if !state.is_changed() && !warmup_done.is_changed() {
+        return;
+    }
+    let phase = match state.0 {
+        SimonState::MonkeySee => Some("Watch"),
+        SimonState::MonkeyDo => Some("Repeat"),
+        _ => None,
+    };
+    for (mut text, mut visibility) in labels.iter_mut() {
+        visibility.is_visible = phase.is_some();
+        if let Some(phase) = phase {
+            text.sections[0].value = if warmup_done.0 {
+                phase.into()
+            } else {
+                format!("Warm-up, not scored: {phase}")
+            };
         }
     }
 }
 
 /// Load assets
-fn load_assets(asset_server: Res<AssetServer>, mut audio_handles: ResMut<AudioHandles>) {
-    audio_handles.red = Some(asset_server.load("sounds/buttons/red.ogg"));
-    audio_handles.green = Some(asset_server.load("sounds/buttons/green.ogg"));
-    audio_handles.blue = Some(asset_server.load("sounds/buttons/blue.ogg"));
-    audio_handles.yellow = Some(asset_server.load("sounds/buttons/yellow.ogg"));
+fn load_assets(
+    asset_server: Res<AssetServer>,
+    mut audio_handles: ResMut<AudioHandles>,
+    settings: Res<Settings>,
+) {
+    audio_handles.load_buttons(&asset_server, settings.sound_pack);
     audio_handles.incorrect = Some(asset_server.load("sounds/incorrect.ogg"));
 }
 
-/// Play button sounds
+/// Reloads the button sounds when the sound pack changes, using the default
+/// pack's sound for any that fail to load
+fn apply_sound_pack(
+    settings: Res<Settings>,
+    asset_server: Res<AssetServer>,
+    mut audio_handles: ResMut<AudioHandles>,
+) {
+    if settings.is_changed() && settings.sound_pack != audio_handles.sound_pack {
+        audio_handles.load_buttons(&asset_server, settings.sound_pack);
+    }
+
+    let sound_pack = audio_handles.sound_pack;
+    for button in Button::ALL {
+        let Some(handle) = audio_handles.button_mut(button) else {
+            continue;
+        };
+        if asset_server.get_load_state(&*handle) != LoadState::Failed {
+            continue;
+        }
+        let fallback = asset_server.load(button_sound_path(SoundPack::default(), button));
+        if *handle != fallback {
+            warn!("Couldn't load the {button:?} sound from the {sound_pack:?} pack, using the default");
+            *handle = fallback;
+        }
+    }
+}
+
+/// Play button sounds, panned towards the button if enabled
+///
+/// Classic hardware games play the original's tones instead
+#[allow(clippy::too_many_arguments)]
 fn play_button_sound(
     mut event_reader: EventReader<ButtonEvent>,
     audio: Res<Audio>,
     audio_handles: Res<AudioHandles>,
+    panned_audio: Res<Audio<PannedSound>>,
+    mut panned_sounds: ResMut<Assets<PannedSound>>,
+    sources: Res<Assets<AudioSource>>,
+    buttons: Query<(&Button, &Transform)>,
+    settings: Res<Settings>,
+    game_mode: Res<GameMode>,
+    app_state: Res<CurrentState<AppState>>,
 ) {
+    if uses_hardware_sounds(*game_mode, app_state.0) {
+        event_reader.clear();
+        return;
+    }
     for event in event_reader.iter() {
         let button = match event {
             ButtonEvent::Pressed(button) => button,
@@ -553,7 +1838,20 @@ fn play_button_sound(
             Button::Blue => &audio_handles.blue,
             Button::Yellow => &audio_handles.yellow,
         } {
-            audio.play(audio_handle.clone());
+            let source = sources.get(audio_handle);
+            let x = buttons
+                .iter()
+                .find(|(b, _)| *b == button)
+                .map(|(_, transform)| transform.translation.x);
+            match (source, x) {
+                (Some(source), Some(x)) if settings.panned_audio => {
+                    let sound = PannedSound::new(source.clone(), x);
+                    panned_audio.play(panned_sounds.add(sound));
+                }
+                _ => {
+                    audio.play(audio_handle.clone());
+                }
+            }
         };
     }
 }
@@ -563,7 +1861,13 @@ fn play_game_sound(
     mut event_reader: EventReader<SimonEvent>,
     audio: Res<Audio>,
     audio_handles: Res<AudioHandles>,
+    game_mode: Res<GameMode>,
+    app_state: Res<CurrentState<AppState>>,
 ) {
+    if uses_hardware_sounds(*game_mode, app_state.0) {
+        event_reader.clear();
+        return;
+    }
     for event in event_reader.iter() {
         if let Some(audio_handle) = match event {
             SimonEvent::Failure => audio_handles.incorrect.as_ref(),
@@ -573,3 +1877,576 @@ fn play_game_sound(
         };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::input::gamepad::{gamepad_connection_system, GamepadInfo};
+
+    use super::*;
+    use crate::{gamepad::gamepad_press_buttons, generator::ListGenerator};
+
+    /// An app with just enough to run `validate_buttons`
+    fn validation_app(pattern: Vec<Button>) -> App {
+        let mut app = App::new();
+        app.add_event::<ButtonEvent>()
+            .add_event::<SimonEvent>()
+            .add_event::<WrongPress>()
+            .insert_resource(Pattern(pattern))
+            .init_resource::<Progress>()
+            .init_resource::<Stats>()
+            .init_resource::<InputHistory>()
+            .init_resource::<RoundForgiveness>()
+            .init_resource::<Settings>()
+            .add_system(validate_buttons);
+        app
+    }
+
+    /// Every `SimonEvent` sent so far
+    fn simon_events(app: &App) -> Vec<SimonEvent> {
+        let events = app.world.resource::<Events<SimonEvent>>();
+        events.get_reader().iter(events).copied().collect()
+    }
+
+    #[test]
+    fn simultaneous_presses_only_validate_the_first() {
+        let mut app = validation_app(vec![Button::Red, Button::Green]);
+        app.world.send_event(ButtonEvent::Pressed(Button::Red));
+        app.world.send_event(ButtonEvent::Pressed(Button::Green));
+        app.update();
+
+        assert_eq!(simon_events(&app), vec![SimonEvent::Next]);
+        assert_eq!(app.world.resource::<Stats>().buttons_pressed, 1);
+    }
+
+    #[test]
+    fn simultaneous_presses_fail_once_on_a_wrong_first_press() {
+        let mut app = validation_app(vec![Button::Red, Button::Green]);
+        app.world.send_event(ButtonEvent::Pressed(Button::Blue));
+        app.world.send_event(ButtonEvent::Pressed(Button::Red));
+        app.update();
+
+        assert_eq!(simon_events(&app), vec![SimonEvent::Failure]);
+    }
+
+    #[test]
+    fn presses_past_the_end_of_the_pattern_are_ignored() {
+        let mut app = validation_app(vec![Button::Red]);
+        app.world.resource_mut::<Progress>().0 = 1;
+        app.world.send_event(ButtonEvent::Pressed(Button::Red));
+        app.update();
+
+        assert!(simon_events(&app).is_empty());
+        assert_eq!(app.world.resource::<Stats>().buttons_pressed, 0);
+    }
+
+    #[test]
+    fn presses_on_an_empty_pattern_are_ignored() {
+        let mut app = validation_app(Vec::new());
+        app.world.send_event(ButtonEvent::Pressed(Button::Red));
+        app.update();
+
+        assert!(simon_events(&app).is_empty());
+    }
+
+    /// An app that only runs `check_progress`
+    fn check_progress_app(pattern_length: usize, progress: usize) -> App {
+        let mut app = App::new();
+        app.insert_resource(Pattern(vec![Button::Red; pattern_length]))
+            .insert_resource(Progress(progress))
+            .add_system(check_progress);
+        app
+    }
+
+    #[test]
+    fn progress_can_reach_the_end_of_the_pattern() {
+        // The demo leaves the progress at the length once it's shown every button
+        check_progress_app(3, 3).update();
+        check_progress_app(0, 0).update();
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn progress_past_the_end_of_the_pattern_panics_in_debug() {
+        check_progress_app(3, 4).update();
+    }
+
+    /// `validation_app` with `game_event_handler` acting on the results
+    fn round_app(pattern: Vec<Button>, settings: Settings) -> App {
+        let mut app = validation_app(pattern);
+        app.insert_resource(settings)
+            .init_resource::<FailedPattern>()
+            .init_resource::<GameMode>()
+            .init_resource::<Lives>()
+            .init_resource::<ConsecutiveFailures>()
+            .init_resource::<WarmupDone>()
+            .init_resource::<Performance>()
+            .add_event::<Toast>()
+            .init_resource::<ButtonTimings>()
+            .add_system(game_event_handler.after(validate_buttons));
+        app
+    }
+
+    /// Presses each button in its own frame
+    fn press_in_turn(app: &mut App, buttons: &[Button]) {
+        for button in buttons {
+            app.world.send_event(ButtonEvent::Pressed(*button));
+            app.update();
+        }
+    }
+
+    #[test]
+    fn repeating_the_pattern_succeeds_and_waits_for_the_next_round() {
+        let mut app = round_app(vec![Button::Red, Button::Green], Settings::default());
+        press_in_turn(&mut app, &[Button::Red, Button::Green]);
+
+        assert_eq!(
+            simon_events(&app),
+            vec![SimonEvent::Next, SimonEvent::Success]
+        );
+        assert_eq!(app.world.resource::<Progress>().0, 0);
+        assert!(app.world.contains_resource::<AdvanceDelay>());
+        assert!(!app.world.contains_resource::<NextState<SimonState>>());
+    }
+
+    #[test]
+    fn repeating_a_pattern_at_the_max_length_switches_to_victory() {
+        let settings = Settings {
+            max_length: Some(2),
+            ..Default::default()
+        };
+        let mut app = round_app(vec![Button::Red, Button::Green], settings);
+        press_in_turn(&mut app, &[Button::Red, Button::Green]);
+
+        assert_eq!(
+            simon_events(&app),
+            vec![SimonEvent::Next, SimonEvent::Success]
+        );
+        assert_eq!(app.world.resource::<Progress>().0, 0);
+        assert_eq!(
+            app.world.resource::<NextState<AppState>>().0,
+            AppState::Victory
+        );
+        assert_eq!(
+            app.world.resource::<NextState<SimonState>>().0,
+            SimonState::Idle
+        );
+    }
+
+    /// `round_app` in the middle of a round, with the states it switches between
+    fn round_state_app(pattern: Vec<Button>, game_mode: GameMode) -> App {
+        let mut app = round_app(pattern, Settings::default());
+        app.insert_resource(game_mode)
+            .init_resource::<Time>()
+            .add_loopless_state(AppState::Playing)
+            .add_loopless_state(SimonState::MonkeyDo)
+            .add_system(advance_after_delay);
+        app
+    }
+
+    #[test]
+    fn success_goes_back_to_monkey_see_after_the_delay() {
+        let mut app = round_state_app(vec![Button::Red, Button::Green], GameMode::Classic);
+        press_in_turn(&mut app, &[Button::Red, Button::Green]);
+        assert_eq!(
+            app.world.resource::<CurrentState<SimonState>>().0,
+            SimonState::MonkeyDo
+        );
+
+        advance_time(&mut app, 5.0);
+        app.update();
+        assert_eq!(
+            app.world.resource::<CurrentState<SimonState>>().0,
+            SimonState::MonkeySee
+        );
+        assert_eq!(
+            app.world.resource::<CurrentState<AppState>>().0,
+            AppState::Playing
+        );
+    }
+
+    #[test]
+    fn failure_with_a_retry_goes_back_to_monkey_see() {
+        let mut app = round_state_app(vec![Button::Red, Button::Green], GameMode::Practice);
+        press_in_turn(&mut app, &[Button::Blue]);
+        assert_eq!(simon_events(&app), vec![SimonEvent::Failure]);
+
+        app.update();
+        assert_eq!(
+            app.world.resource::<CurrentState<SimonState>>().0,
+            SimonState::MonkeySee
+        );
+        assert_eq!(
+            app.world.resource::<CurrentState<AppState>>().0,
+            AppState::Playing
+        );
+        assert!(app.world.contains_resource::<RepeatPattern>());
+    }
+
+    #[test]
+    fn strict_hardware_ends_the_game_on_the_first_mistake() {
+        let mut app = round_state_app(vec![Button::Red, Button::Green], GameMode::Hardware);
+        app.insert_resource(Settings {
+            second_chance: true,
+            failure_policy: FailurePolicy::KeepPattern,
+            ..Default::default()
+        });
+        app.world.resource_mut::<Lives>().current = 1;
+        press_in_turn(&mut app, &[Button::Blue]);
+        assert_eq!(simon_events(&app), vec![SimonEvent::Failure]);
+
+        app.update();
+        assert_eq!(
+            app.world.resource::<CurrentState<SimonState>>().0,
+            SimonState::Review
+        );
+        assert!(!app.world.contains_resource::<RepeatPattern>());
+    }
+
+    #[test]
+    fn new_patterns_start_at_the_starting_level_and_grow_by_one() {
+        let buttons = vec![Button::Blue, Button::Red, Button::Yellow, Button::Green];
+        let mut app = App::new();
+        app.init_resource::<Pattern>()
+            .init_resource::<Progress>()
+            .init_resource::<WarmupDone>()
+            .insert_resource(Settings {
+                starting_level: 3,
+                ..Default::default()
+            })
+            .insert_resource(PatternGenerator(std::boxed::Box::new(ListGenerator(
+                buttons.clone(),
+            ))))
+            .add_system(update_pattern);
+
+        app.update();
+        assert_eq!(app.world.resource::<Pattern>().0, buttons[..3]);
+        app.update();
+        assert_eq!(app.world.resource::<Pattern>().0, buttons);
+    }
+
+    #[test]
+    fn the_turn_timer_follows_game_speed_accessibility_timing_and_pace() {
+        let timings = ButtonTimings::default();
+        assert_eq!(timings.turn_time(5.0, &Settings::default()), 5.0);
+
+        let faster = Settings {
+            game_speed: GameSpeed(2.0),
+            ..Default::default()
+        };
+        assert_eq!(timings.turn_time(5.0, &faster), 2.5);
+
+        let stretched = Settings {
+            accessibility_timing: 2.0,
+            ..Default::default()
+        };
+        assert_eq!(timings.turn_time(5.0, &stretched), 10.0);
+
+        let both = Settings {
+            game_speed: GameSpeed(2.0),
+            accessibility_timing: 2.0,
+            ..Default::default()
+        };
+        let paced = ButtonTimings {
+            pace: 0.5,
+            ..Default::default()
+        };
+        assert_eq!(paced.turn_time(8.0, &both), 4.0);
+    }
+
+    /// An app with one red button and the systems that animate it
+    fn button_app() -> (App, Entity) {
+        let mut app = App::new();
+        app.add_event::<ButtonEvent>()
+            .init_resource::<Time>()
+            .init_resource::<ButtonTimings>()
+            .init_resource::<Pattern>()
+            .init_resource::<Settings>()
+            .init_resource::<DemoSpeedup>()
+            .add_system(button_event_handler)
+            .add_system(button_state_manager.after(button_event_handler));
+        let button = app
+            .world
+            .spawn((
+                Button::Red,
+                ButtonState::Inactive,
+                PreviousButtonState(ButtonState::Inactive),
+                QueuedPresses::default(),
+            ))
+            .id();
+        (app, button)
+    }
+
+    #[test]
+    fn back_to_back_presses_each_show_a_press() {
+        let (mut app, button) = button_app();
+        app.world.send_event(ButtonEvent::Pressed(Button::Red));
+        app.update();
+        let Some(ButtonState::Pressed { duration, .. }) =
+            app.world.get::<ButtonState>(button).copied()
+        else {
+            panic!("the first press should push the button down");
+        };
+
+        // The second press is too soon to restart the first, so it waits
+        app.world.send_event(ButtonEvent::Pressed(Button::Red));
+        app.update();
+        assert_eq!(app.world.get::<QueuedPresses>(button).unwrap().0, 1);
+
+        // Once the first press has shown long enough, the second one starts
+        *app.world.get_mut::<ButtonState>(button).unwrap() = ButtonState::Pressed {
+            timer: duration - MIN_PRESS_SECONDS * 2.0,
+            duration,
+        };
+        app.update();
+        assert_eq!(app.world.get::<QueuedPresses>(button).unwrap().0, 0);
+        assert!(matches!(
+            app.world.get::<ButtonState>(button),
+            Some(ButtonState::Pressed { timer, .. }) if *timer == duration
+        ));
+    }
+
+    /// Moves the app's clock on by exactly `seconds`, then runs a frame
+    fn advance_time(app: &mut App, seconds: f32) {
+        let mut time = app.world.resource_mut::<Time>();
+        let last_update = time.last_update().unwrap_or_else(|| {
+            // The first update only starts the clock
+            let startup = time.startup();
+            time.update_with_instant(startup);
+            startup
+        });
+        time.update_with_instant(last_update + Duration::from_secs_f32(seconds));
+        app.update();
+    }
+
+    #[test]
+    fn pressed_buttons_go_back_to_inactive_when_their_timer_runs_out() {
+        let (mut app, button) = button_app();
+        *app.world.get_mut::<ButtonState>(button).unwrap() = ButtonState::Pressed {
+            timer: 0.5,
+            duration: 0.5,
+        };
+
+        advance_time(&mut app, 0.25);
+        assert!(matches!(
+            app.world.get::<ButtonState>(button),
+            Some(ButtonState::Pressed { .. })
+        ));
+
+        // The timer runs out, and the next frame puts the button back
+        advance_time(&mut app, 0.3);
+        advance_time(&mut app, 1.0 / 60.0);
+        assert!(matches!(
+            app.world.get::<ButtonState>(button),
+            Some(ButtonState::Inactive)
+        ));
+        assert!(matches!(
+            app.world.get::<PreviousButtonState>(button),
+            Some(PreviousButtonState(ButtonState::Pressed { timer, .. })) if *timer <= 0.0
+        ));
+    }
+
+    #[test]
+    fn lit_buttons_go_back_to_inactive_when_their_timer_runs_out() {
+        let (mut app, button) = button_app();
+        *app.world.get_mut::<ButtonState>(button).unwrap() = ButtonState::Lit {
+            timer: 0.8,
+            duration: 0.8,
+        };
+
+        // Each frame takes its time off the timer
+        for _ in 0..3 {
+            advance_time(&mut app, 0.25);
+        }
+        assert!(matches!(
+            app.world.get::<ButtonState>(button),
+            Some(ButtonState::Lit { timer, .. }) if (*timer - 0.05).abs() < 1e-4
+        ));
+        assert!(matches!(
+            app.world.get::<PreviousButtonState>(button),
+            Some(PreviousButtonState(ButtonState::Inactive))
+        ));
+
+        // The timer runs out, and the next frame puts the button back
+        advance_time(&mut app, 0.25);
+        advance_time(&mut app, 0.25);
+        assert!(matches!(
+            app.world.get::<ButtonState>(button),
+            Some(ButtonState::Inactive)
+        ));
+        assert!(matches!(
+            app.world.get::<PreviousButtonState>(button),
+            Some(PreviousButtonState(ButtonState::Lit { timer, .. })) if *timer <= 0.0
+        ));
+    }
+
+    /// Clicks the button, then lets go of it
+    fn click(app: &mut App, button: Entity) {
+        for interaction in [Interaction::Clicked, Interaction::None] {
+            *app.world.get_mut::<Interaction>(button).unwrap() = interaction;
+            app.update();
+        }
+    }
+
+    /// Button presses sent so far, counted as they happen since events
+    /// only last two frames
+    #[derive(Resource, Default)]
+    struct PressCount(usize);
+
+    fn count_presses(mut event_reader: EventReader<ButtonEvent>, mut count: ResMut<PressCount>) {
+        count.0 += event_reader
+            .iter()
+            .filter(|event| matches!(event, ButtonEvent::Pressed(_)))
+            .count();
+    }
+
+    #[test]
+    fn presses_within_the_cooldown_count_as_one() {
+        let mut app = App::new();
+        app.add_event::<ButtonEvent>()
+            .init_resource::<Settings>()
+            .init_resource::<ButtonTimings>()
+            .init_resource::<Gamepads>()
+            .init_resource::<Input<GamepadButton>>()
+            .init_resource::<PressCount>()
+            .add_system(press_buttons)
+            .add_system(count_presses.after(press_buttons));
+        let button = app
+            .world
+            .spawn((
+                Button::Red,
+                ButtonState::Inactive,
+                Interaction::None,
+                PressCooldown::default(),
+            ))
+            .id();
+
+        click(&mut app, button);
+        click(&mut app, button);
+        assert_eq!(app.world.resource::<PressCount>().0, 1);
+
+        // Once the cooldown is over the button takes presses again
+        let cooldown = &mut app.world.get_mut::<PressCooldown>(button).unwrap().0;
+        let duration = cooldown.duration();
+        cooldown.tick(duration);
+        click(&mut app, button);
+        assert_eq!(app.world.resource::<PressCount>().0, 2);
+    }
+
+    /// An app with a red button pressed by clicks and a connected gamepad
+    fn press_app() -> (App, Entity) {
+        let mut app = App::new();
+        app.add_event::<ButtonEvent>()
+            .add_event::<GamepadEvent>()
+            .init_resource::<Settings>()
+            .init_resource::<ButtonTimings>()
+            .init_resource::<Gamepads>()
+            .init_resource::<Input<GamepadButton>>()
+            .init_resource::<PressCount>()
+            .add_system(gamepad_connection_system)
+            .add_system(press_buttons)
+            .add_system(gamepad_press_buttons)
+            .add_system(
+                count_presses
+                    .after(press_buttons)
+                    .after(gamepad_press_buttons),
+            );
+        let button = app
+            .world
+            .spawn((
+                Button::Red,
+                ButtonState::Inactive,
+                Interaction::None,
+                PressCooldown::default(),
+            ))
+            .id();
+        app.world.send_event(GamepadEvent::new(
+            Gamepad::new(0),
+            GamepadEventType::Connected(GamepadInfo {
+                name: "Test".into(),
+            }),
+        ));
+        app.update();
+        (app, button)
+    }
+
+    /// Lets the button take presses again straight away
+    fn finish_cooldown(app: &mut App, button: Entity) {
+        let cooldown = &mut app.world.get_mut::<PressCooldown>(button).unwrap().0;
+        let duration = cooldown.duration();
+        cooldown.tick(duration);
+    }
+
+    /// Holds or lets go of the red button on the gamepad, for one frame
+    fn set_gamepad_red(app: &mut App, held: bool) {
+        let red = GamepadButton::new(Gamepad::new(0), GamepadButtonType::East);
+        let mut input = app.world.resource_mut::<Input<GamepadButton>>();
+        // Only the frame it changes on is just pressed
+        input.clear();
+        if held {
+            input.press(red);
+        } else {
+            input.release(red);
+        }
+    }
+
+    #[test]
+    fn holding_a_click_presses_once() {
+        let (mut app, button) = press_app();
+        *app.world.get_mut::<Interaction>(button).unwrap() = Interaction::Clicked;
+        app.update();
+        for _ in 0..3 {
+            // Even if picking touches the interaction again while held
+            finish_cooldown(&mut app, button);
+            *app.world.get_mut::<Interaction>(button).unwrap() = Interaction::Clicked;
+            app.update();
+        }
+        assert_eq!(app.world.resource::<PressCount>().0, 1);
+
+        // Letting go and clicking again presses again
+        *app.world.get_mut::<Interaction>(button).unwrap() = Interaction::Hovered;
+        app.update();
+        click(&mut app, button);
+        assert_eq!(app.world.resource::<PressCount>().0, 2);
+    }
+
+    #[test]
+    fn holding_a_gamepad_button_presses_once() {
+        let (mut app, button) = press_app();
+        set_gamepad_red(&mut app, true);
+        app.update();
+        for _ in 0..3 {
+            finish_cooldown(&mut app, button);
+            set_gamepad_red(&mut app, true);
+            app.update();
+        }
+        assert_eq!(app.world.resource::<PressCount>().0, 1);
+
+        set_gamepad_red(&mut app, false);
+        app.update();
+        set_gamepad_red(&mut app, true);
+        app.update();
+        assert_eq!(app.world.resource::<PressCount>().0, 2);
+    }
+
+    #[test]
+    fn a_held_button_ignores_other_inputs_until_let_go() {
+        let (mut app, button) = press_app();
+        set_gamepad_red(&mut app, true);
+        app.update();
+        finish_cooldown(&mut app, button);
+        set_gamepad_red(&mut app, true);
+        click(&mut app, button);
+        assert_eq!(app.world.resource::<PressCount>().0, 1);
+
+        set_gamepad_red(&mut app, false);
+        app.update();
+        *app.world.get_mut::<Interaction>(button).unwrap() = Interaction::Clicked;
+        app.update();
+        assert_eq!(app.world.resource::<PressCount>().0, 2);
+        finish_cooldown(&mut app, button);
+        set_gamepad_red(&mut app, true);
+        app.update();
+        assert_eq!(app.world.resource::<PressCount>().0, 2);
+    }
+}
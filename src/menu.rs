@@ -0,0 +1,1125 @@
+use bevy::{app::AppExit, prelude::*, window::WindowFocused};
+use iyes_loopless::prelude::*;
+
+use crate::{
+    challenge::Challenge,
+    effects::Milestones,
+    fonts::ThemedText,
+    fonts::UiFont,
+    generator::Daily,
+    leave_game,
+    peek::Peeks,
+    save::{SaveData, Stats},
+    seed::SeedDialog,
+    settings::{Setting, Settings, SettingsPage},
+    AppState, BoostedStart, FailedPattern, FixedUpdate, GameMode, Lives, Pattern, Progress,
+    RepeatPattern, RunTime, Score, SimonState,
+};
+
+/// Menus and the screens between rounds
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<MenuAction>()
+            .init_resource::<Continues>()
+            .add_enter_system(AppState::Menu, spawn_main_menu)
+            .add_exit_system(AppState::Menu, despawn_screen::<MainMenuScreen>)
+            .add_exit_system(AppState::Menu, despawn_screen::<QuitDialog>)
+            .add_enter_system(AppState::GameOver, spawn_game_over_screen)
+            .add_exit_system(AppState::GameOver, despawn_screen::<GameOverScreen>)
+            .add_enter_system(AppState::Victory, spawn_victory_screen)
+            .add_exit_system(AppState::Victory, despawn_screen::<VictoryScreen>)
+            .init_resource::<SettingsPage>()
+            .add_enter_system(AppState::Settings, spawn_settings_screen)
+            .add_exit_system(AppState::Settings, despawn_screen::<SettingsScreen>)
+            .add_exit_system(AppState::Settings, despawn_screen::<ResetDialog>)
+            .add_enter_system(AppState::Stats, spawn_stats_screen)
+            .add_exit_system(AppState::Stats, despawn_screen::<StatsScreen>)
+            .init_resource::<LeaderboardTab>()
+            .add_enter_system(AppState::Leaderboard, spawn_leaderboard_screen)
+            .add_exit_system(AppState::Leaderboard, despawn_screen::<LeaderboardScreen>)
+            .add_enter_system(AppState::Paused, spawn_pause_screen)
+            .add_exit_system(AppState::Paused, despawn_screen::<PauseScreen>)
+            .add_exit_system(AppState::Paused, unpause_fixed_timestep)
+            .add_system(update_setting_labels)
+            .add_system(press_menu_buttons)
+            .init_resource::<MenuSelection>()
+            .add_system(navigate_menus)
+            .add_system(hold_to_confirm.after(navigate_menus))
+            .add_system(menu_action_handler.label(MenuActions))
+            .add_system(menu_shortcuts)
+            .add_system(quit_dialog_handler)
+            .add_system(reset_dialog_handler)
+            .add_system(pause_handler)
+            .add_system(pause_on_focus_loss);
+    }
+}
+
+/// Clickable menu items and the events they fire
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MenuAction {
+    Play,
+    Practice,
+    Zen,
+    Hardware,
+    Composer,
+    Challenge,
+    SeedPrompt,
+    ConfirmSeed,
+    CancelSeed,
+    RerollSeed,
+    CopyResult,
+    PlayAgain,
+    Retry,
+    Continue,
+    Settings,
+    Stats,
+    Leaderboard,
+    ShowLeaderboard(LeaderboardTab),
+    ShowSettings(SettingsPage),
+    WatchBestRun,
+    MainMenu,
+    Back,
+    Change(Setting),
+    QuitPrompt,
+    Quit,
+    CancelQuit,
+    ResetPrompt,
+    ResetProgress,
+    CancelReset,
+    Pause,
+    Resume,
+}
+
+/// Label for the system that acts on `MenuAction`s
+#[derive(SystemLabel)]
+pub struct MenuActions;
+
+/// Continues used in the current run, and what they cost
+#[derive(Resource)]
+pub struct Continues {
+    pub used: usize,
+    pub limit: usize,
+    pub cost: usize,
+}
+
+impl Default for Continues {
+    fn default() -> Self {
+        Self {
+            used: 0,
+            limit: 1,
+            cost: 3,
+        }
+    }
+}
+
+impl Continues {
+    /// Whether a continue can be bought with this score
+    fn available(&self, score: &Score) -> bool {
+        self.used < self.limit && score.current >= self.cost
+    }
+}
+
+/// The menu item picked with the keyboard or mouse
+#[derive(Resource, Default)]
+pub struct MenuSelection(pub usize);
+
+/// Menu items and everything needed to sort and highlight them
+type MenuItems<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static MenuAction,
+        &'static GlobalTransform,
+        &'static Interaction,
+        ChangeTrackers<Interaction>,
+        &'static mut BackgroundColor,
+        &'static ComputedVisibility,
+        Option<&'static mut HoldToConfirm>,
+    ),
+>;
+
+/// A menu item that has to be held down for a moment before it fires
+#[derive(Component)]
+struct HoldToConfirm {
+    timer: Timer,
+    /// Whether Enter is holding it, set by `navigate_menus`
+    keyboard: bool,
+}
+
+/// The bar that fills up while a `HoldToConfirm` item is held
+#[derive(Component)]
+struct HoldFill;
+
+/// The screen to go back to when leaving the settings
+#[derive(Resource)]
+struct SettingsReturn(AppState);
+
+/// Marker component for the main menu
+#[derive(Component)]
+pub struct MainMenuScreen;
+
+/// Marker component for the game over screen
+#[derive(Component)]
+struct GameOverScreen;
+
+/// Marker component for the settings screen
+#[derive(Component)]
+struct SettingsScreen;
+
+/// Marker component for the victory screen
+#[derive(Component)]
+struct VictoryScreen;
+
+/// Marker component for the stats screen
+#[derive(Component)]
+struct StatsScreen;
+
+/// Marker component for the leaderboard screen
+#[derive(Component)]
+struct LeaderboardScreen;
+
+/// The leaderboard being shown
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LeaderboardTab {
+    #[default]
+    HighestScore,
+    FastestTime,
+}
+
+impl LeaderboardTab {
+    const ALL: [LeaderboardTab; 2] = [LeaderboardTab::HighestScore, LeaderboardTab::FastestTime];
+
+    fn label(&self) -> &'static str {
+        match self {
+            LeaderboardTab::HighestScore => "Highest Score",
+            LeaderboardTab::FastestTime => "Fastest Time",
+        }
+    }
+}
+
+/// Marker component for the quit confirmation dialog
+#[derive(Component)]
+struct QuitDialog;
+
+/// Marker component for the reset progress confirmation dialog
+#[derive(Component)]
+struct ResetDialog;
+
+/// Marker component for the pause screen
+#[derive(Component)]
+struct PauseScreen;
+
+const MENU_BUTTON_COLOR: Color = Color::rgb(0.15, 0.15, 0.15);
+const MENU_BUTTON_SELECTED: Color = Color::rgb(0.3, 0.3, 0.3);
+const ENTER_KEYS: [KeyCode; 2] = [KeyCode::Return, KeyCode::NumpadEnter];
+const HOLD_FILL_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.25);
+/// How long "Play Again" has to be held, so it isn't hit by accident
+const HOLD_TO_CONFIRM_SECONDS: f32 = 0.5;
+
+/// Spawns the main menu
+fn spawn_main_menu(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    save_data: Res<SaveData>,
+    settings: Res<Settings>,
+) {
+    let title_style = menu_text_style(&font, 96.0, settings.ui_text_color.color());
+    let label_style = menu_text_style(&font, 28.0, settings.ui_text_color.color());
+
+    // Keep the desk visible for the attract mode demo
+    let mut root = fullscreen_column();
+    root.background_color = Color::NONE.into();
+    root.style.justify_content = JustifyContent::FlexStart;
+    root.style.padding.top = Val::Percent(4.0);
+
+    commands
+        .spawn(root)
+        .insert(MainMenuScreen)
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("Simon", title_style));
+            spawn_menu_button(parent, MenuAction::Play, "Play", &label_style);
+            spawn_menu_button(parent, MenuAction::Practice, "Practice", &label_style);
+            spawn_menu_button(parent, MenuAction::Zen, "Zen", &label_style);
+            spawn_menu_button(
+                parent,
+                MenuAction::Hardware,
+                "Classic Hardware",
+                &label_style,
+            );
+            spawn_menu_button(parent, MenuAction::Composer, "Composer", &label_style);
+            spawn_menu_button(parent, MenuAction::Challenge, "Challenge", &label_style);
+            spawn_menu_button(parent, MenuAction::SeedPrompt, "Enter Seed", &label_style);
+            spawn_menu_button(parent, MenuAction::RerollSeed, "Re-roll Seed", &label_style);
+            spawn_menu_button(parent, MenuAction::Settings, "Settings", &label_style);
+            spawn_menu_button(parent, MenuAction::Stats, "Stats", &label_style);
+            spawn_menu_button(parent, MenuAction::Leaderboard, "Leaderboard", &label_style);
+            if !save_data.best_run.presses.is_empty() {
+                spawn_menu_button(
+                    parent,
+                    MenuAction::WatchBestRun,
+                    "Watch Best Run",
+                    &label_style,
+                );
+            }
+            spawn_menu_button(parent, MenuAction::QuitPrompt, "Quit", &label_style);
+        });
+}
+
+/// Spawns the game over screen
+fn spawn_game_over_screen(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    score: Res<Score>,
+    continues: Res<Continues>,
+    daily: Option<Res<Daily>>,
+    challenge: Option<Res<Challenge>>,
+    settings: Res<Settings>,
+) {
+    let title_style = menu_text_style(&font, 64.0, settings.ui_text_color.color());
+    let label_style = menu_text_style(&font, 28.0, settings.ui_text_color.color());
+
+    commands
+        .spawn(fullscreen_column())
+        .insert(GameOverScreen)
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("Game Over", title_style.clone()));
+            parent.spawn(TextBundle::from_section(
+                format!("Score: {}", score.current),
+                label_style.clone(),
+            ));
+
+            spawn_hold_button(parent, MenuAction::PlayAgain, "Play Again", &label_style);
+            spawn_menu_button(
+                parent,
+                MenuAction::Retry,
+                "Retry Same Pattern",
+                &label_style,
+            );
+            if continues.available(&score) {
+                spawn_menu_button(
+                    parent,
+                    MenuAction::Continue,
+                    &format!(
+                        "Continue (-{} score, {} left)",
+                        continues.cost,
+                        continues.limit - continues.used
+                    ),
+                    &label_style,
+                );
+            }
+            // Challenges don't use the daily pattern, so there's nothing to share
+            if daily.is_some() && challenge.is_none() {
+                spawn_menu_button(parent, MenuAction::CopyResult, "Copy Result", &label_style);
+            }
+            spawn_menu_button(parent, MenuAction::Settings, "Settings", &label_style);
+            spawn_menu_button(parent, MenuAction::MainMenu, "Main Menu", &label_style);
+        });
+}
+
+/// Spawns the congratulations screen for reaching the max pattern length
+fn spawn_victory_screen(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    score: Res<Score>,
+    run_time: Res<RunTime>,
+    settings: Res<Settings>,
+) {
+    let title_style = menu_text_style(&font, 64.0, settings.ui_text_color.color());
+    let label_style = menu_text_style(&font, 28.0, settings.ui_text_color.color());
+    let seconds = run_time.0.elapsed().as_secs();
+
+    commands
+        .spawn(fullscreen_column())
+        .insert(VictoryScreen)
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("You Win!", title_style));
+            parent.spawn(TextBundle::from_section(
+                format!("Score: {}", score.current),
+                label_style.clone(),
+            ));
+            parent.spawn(TextBundle::from_section(
+                format!("Time: {}", format_time(seconds)),
+                label_style.clone(),
+            ));
+            spawn_menu_button(parent, MenuAction::PlayAgain, "Play Again", &label_style);
+            spawn_menu_button(parent, MenuAction::MainMenu, "Main Menu", &label_style);
+        });
+}
+
+/// Spawns the settings on the `SettingsPage`, with buttons to the pages either side
+fn spawn_settings_screen(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    settings: Res<Settings>,
+    page: Res<SettingsPage>,
+) {
+    let title_style = menu_text_style(&font, 64.0, settings.ui_text_color.color());
+    let label_style = menu_text_style(&font, 28.0, settings.ui_text_color.color());
+
+    commands
+        .spawn(fullscreen_column())
+        .insert(SettingsScreen)
+        .with_children(|parent| {
+            parent
+                .spawn(TextBundle::from_section(
+                    format!("Settings: {}", page.label()),
+                    title_style,
+                ))
+                .insert(ThemedText);
+            // Wrap into more columns when there are too many to fit
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(90.0), Val::Percent(65.0)),
+                        flex_direction: FlexDirection::Column,
+                        flex_wrap: FlexWrap::Wrap,
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        align_content: AlignContent::Center,
+                        ..Default::default()
+                    },
+                    background_color: Color::NONE.into(),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    for setting in Setting::ALL
+                        .into_iter()
+                        .filter(|setting| setting.page() == *page)
+                    {
+                        spawn_menu_button(
+                            parent,
+                            MenuAction::Change(setting),
+                            &setting.label(&settings),
+                            &label_style,
+                        );
+                    }
+                });
+            spawn_menu_button(
+                parent,
+                MenuAction::ResetPrompt,
+                "Reset Progress",
+                &label_style,
+            );
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        ..Default::default()
+                    },
+                    background_color: Color::NONE.into(),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    let (previous, next) = (page.step(-1), page.step(1));
+                    spawn_menu_button(
+                        parent,
+                        MenuAction::ShowSettings(previous),
+                        &format!("< {}", previous.label()),
+                        &label_style,
+                    );
+                    spawn_menu_button(parent, MenuAction::Back, "Back", &label_style);
+                    spawn_menu_button(
+                        parent,
+                        MenuAction::ShowSettings(next),
+                        &format!("{} >", next.label()),
+                        &label_style,
+                    );
+                });
+        });
+}
+
+/// Spawns the lifetime stats screen
+fn spawn_stats_screen(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    stats: Res<Stats>,
+    settings: Res<Settings>,
+) {
+    let title_style = menu_text_style(&font, 64.0, settings.ui_text_color.color());
+    let label_style = menu_text_style(&font, 28.0, settings.ui_text_color.color());
+
+    commands
+        .spawn(fullscreen_column())
+        .insert(StatsScreen)
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("Stats", title_style));
+            for line in [
+                format!("Games played: {}", stats.games_played),
+                format!("Buttons pressed: {}", stats.buttons_pressed),
+                format!("Longest pattern: {}", stats.longest_pattern),
+                format!("Failures: {}", stats.failures),
+            ] {
+                parent.spawn(TextBundle::from_section(line, label_style.clone()));
+            }
+            spawn_menu_button(parent, MenuAction::Back, "Back", &label_style);
+        });
+}
+
+/// Spawns the leaderboard picked by `LeaderboardTab`, with a button for each tab
+fn spawn_leaderboard_screen(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    save_data: Res<SaveData>,
+    tab: Res<LeaderboardTab>,
+    settings: Res<Settings>,
+) {
+    let title_style = menu_text_style(&font, 64.0, settings.ui_text_color.color());
+    let label_style = menu_text_style(&font, 28.0, settings.ui_text_color.color());
+    let leaderboards = &save_data.leaderboards;
+
+    let lines: Vec<String> = match *tab {
+        LeaderboardTab::HighestScore => leaderboards
+            .high_scores
+            .iter()
+            .enumerate()
+            .map(|(rank, score)| format!("{}. {score}", rank + 1))
+            .collect(),
+        LeaderboardTab::FastestTime => leaderboards
+            .fastest_times
+            .iter()
+            .map(|(target, times)| {
+                let times: Vec<String> = times
+                    .iter()
+                    .map(|seconds| format_time(*seconds as u64))
+                    .collect();
+                format!("Length {target}: {}", times.join(", "))
+            })
+            .collect(),
+    };
+
+    commands
+        .spawn(fullscreen_column())
+        .insert(LeaderboardScreen)
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(tab.label(), title_style));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        ..Default::default()
+                    },
+                    background_color: Color::NONE.into(),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    for other in LeaderboardTab::ALL {
+                        if other != *tab {
+                            spawn_menu_button(
+                                parent,
+                                MenuAction::ShowLeaderboard(other),
+                                other.label(),
+                                &label_style,
+                            );
+                        }
+                    }
+                });
+            if lines.is_empty() {
+                parent.spawn(TextBundle::from_section(
+                    "No records yet",
+                    label_style.clone(),
+                ));
+            }
+            for line in lines {
+                parent.spawn(TextBundle::from_section(line, label_style.clone()));
+            }
+            spawn_menu_button(parent, MenuAction::Back, "Back", &label_style);
+        });
+}
+
+/// Formats seconds as `m:ss`
+fn format_time(seconds: u64) -> String {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// Text style for menus, in the `UiTextColor` as `color`
+pub fn menu_text_style(font: &UiFont, font_size: f32, color: Color) -> TextStyle {
+    TextStyle {
+        font: font.handle.clone(),
+        font_size,
+        color,
+    }
+}
+
+/// A transparent node covering the screen that centers its children
+pub fn fullscreen_column() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+        ..Default::default()
+    }
+}
+
+/// Spawns a clickable menu item with a text label
+pub fn spawn_menu_button(
+    parent: &mut ChildBuilder,
+    action: MenuAction,
+    label: &str,
+    text_style: &TextStyle,
+) {
+    parent
+        .spawn(menu_button_bundle())
+        .insert(action)
+        .with_children(|parent| {
+            parent
+                .spawn(TextBundle::from_section(label, text_style.clone()))
+                .insert(ThemedText);
+        });
+}
+
+/// Spawns a menu item that only fires after being held for a moment
+fn spawn_hold_button(
+    parent: &mut ChildBuilder,
+    action: MenuAction,
+    label: &str,
+    text_style: &TextStyle,
+) {
+    parent
+        .spawn(menu_button_bundle())
+        .insert((
+            action,
+            HoldToConfirm {
+                timer: Timer::from_seconds(HOLD_TO_CONFIRM_SECONDS, TimerMode::Once),
+                keyboard: false,
+            },
+        ))
+        .with_children(|parent| {
+            // Spawned first so the label is drawn on top
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: UiRect {
+                            left: Val::Px(0.0),
+                            top: Val::Px(0.0),
+                            ..Default::default()
+                        },
+                        size: Size::new(Val::Percent(0.0), Val::Percent(100.0)),
+                        ..Default::default()
+                    },
+                    background_color: HOLD_FILL_COLOR.into(),
+                    ..Default::default()
+                })
+                .insert(HoldFill);
+            parent.spawn(TextBundle::from_section(label, text_style.clone()));
+        });
+}
+
+/// The look shared by every menu item
+fn menu_button_bundle() -> ButtonBundle {
+    ButtonBundle {
+        style: Style {
+            margin: UiRect::all(Val::Px(8.0)),
+            padding: UiRect::all(Val::Px(12.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        background_color: MENU_BUTTON_COLOR.into(),
+        ..Default::default()
+    }
+}
+
+/// Despawns every entity with the marker component `T`
+fn despawn_screen<T: Component>(mut commands: Commands, screens: Query<Entity, With<T>>) {
+    for entity in screens.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Fires `MenuAction` events when menu items are clicked, except for
+/// `HoldToConfirm` items
+fn press_menu_buttons(
+    interactions: Query<(&Interaction, &MenuAction, Option<&HoldToConfirm>), Changed<Interaction>>,
+    mut menu_event_writer: EventWriter<MenuAction>,
+) {
+    for (interaction, action, hold) in interactions.iter() {
+        if *interaction == Interaction::Clicked && hold.is_none() {
+            menu_event_writer.send(*action);
+        }
+    }
+}
+
+/// Moves the `MenuSelection` with the arrow keys or WASD, activates it with Enter,
+/// and tints the selected item
+fn navigate_menus(
+    keys: Res<Input<KeyCode>>,
+    mut selection: ResMut<MenuSelection>,
+    mut items: MenuItems,
+    added: Query<(), Added<MenuAction>>,
+    mut menu_event_writer: EventWriter<MenuAction>,
+) {
+    // Start from the top whenever new items appear
+    if !added.is_empty() {
+        selection.0 = 0;
+    }
+
+    // Top to bottom, then left to right, skipping hidden items
+    let mut sorted: Vec<_> = items
+        .iter_mut()
+        .filter(|(_, _, _, _, _, visibility, _)| visibility.is_visible())
+        .collect();
+    sorted.sort_by(|(_, a, ..), (_, b, ..)| {
+        let (a, b) = (a.translation(), b.translation());
+        a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x))
+    });
+    if sorted.is_empty() {
+        return;
+    }
+
+    // Hovering with the mouse moves the selection too
+    if let Some(hovered) = sorted.iter().position(|(_, _, interaction, tracker, ..)| {
+        tracker.is_changed() && **interaction != Interaction::None
+    }) {
+        selection.0 = hovered;
+    }
+
+    let count = sorted.len();
+    if keys.any_just_pressed([KeyCode::Up, KeyCode::W, KeyCode::Left, KeyCode::A]) {
+        selection.0 = (selection.0 + count - 1) % count;
+    }
+    if keys.any_just_pressed([KeyCode::Down, KeyCode::S, KeyCode::Right, KeyCode::D]) {
+        selection.0 += 1;
+    }
+    selection.0 %= count;
+
+    for (index, (action, _, _, _, _, _, hold)) in sorted.iter_mut().enumerate() {
+        let selected = index == selection.0;
+        match hold {
+            // Held items fire from `hold_to_confirm` instead
+            Some(hold) => hold.keyboard = selected && keys.any_pressed(ENTER_KEYS),
+            None if selected && keys.any_just_pressed(ENTER_KEYS) => {
+                menu_event_writer.send(**action);
+            }
+            None => {}
+        }
+    }
+
+    for (index, (_, _, _, _, color, ..)) in sorted.iter_mut().enumerate() {
+        let tint = if index == selection.0 {
+            MENU_BUTTON_SELECTED
+        } else {
+            MENU_BUTTON_COLOR
+        };
+        if color.0 != tint {
+            color.0 = tint;
+        }
+    }
+}
+
+/// Fills `HoldToConfirm` items while they're held and fires them once full,
+/// starting over if they're let go early
+fn hold_to_confirm(
+    mut holds: Query<(&MenuAction, &Interaction, &mut HoldToConfirm, &Children)>,
+    mut fills: Query<&mut Style, With<HoldFill>>,
+    mut menu_event_writer: EventWriter<MenuAction>,
+    time: Res<Time>,
+) {
+    for (action, interaction, mut hold, children) in holds.iter_mut() {
+        if *interaction == Interaction::Clicked || hold.keyboard {
+            // A one-shot timer only just finishes once, so holding on doesn't repeat it
+            if hold.timer.tick(time.delta()).just_finished() {
+                menu_event_writer.send(*action);
+            }
+        } else {
+            hold.timer.reset();
+        }
+
+        let width = Val::Percent(hold.timer.percent() * 100.0);
+        for &child in children.iter() {
+            if let Ok(mut style) = fills.get_mut(child) {
+                if style.size.width != width {
+                    style.size.width = width;
+                }
+            }
+        }
+    }
+}
+
+/// Keeps the settings screen's labels in sync with the `Settings`
+fn update_setting_labels(
+    settings: Res<Settings>,
+    buttons: Query<(&MenuAction, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    for (action, children) in buttons.iter() {
+        if let MenuAction::Change(setting) = action {
+            for child in children.iter() {
+                if let Ok(mut text) = texts.get_mut(*child) {
+                    text.sections[0].value = setting.label(&settings);
+                }
+            }
+        }
+    }
+}
+
+/// Handles `MenuAction`s
+#[allow(clippy::too_many_arguments)]
+fn menu_action_handler(
+    mut commands: Commands,
+    mut event_reader: EventReader<MenuAction>,
+    state: Res<CurrentState<AppState>>,
+    settings_return: Option<Res<SettingsReturn>>,
+    mut settings: ResMut<Settings>,
+    mut pattern: ResMut<Pattern>,
+    mut progress: ResMut<Progress>,
+    mut score: ResMut<Score>,
+    mut continues: ResMut<Continues>,
+    mut milestones: ResMut<Milestones>,
+    mut game_mode: ResMut<GameMode>,
+    mut lives: ResMut<Lives>,
+    mut run_time: ResMut<RunTime>,
+    mut boosted_start: ResMut<BoostedStart>,
+    mut peeks: ResMut<Peeks>,
+    failed_pattern: Res<FailedPattern>,
+) {
+    for event in event_reader.iter() {
+        match event {
+            MenuAction::Play
+            | MenuAction::Practice
+            | MenuAction::Zen
+            | MenuAction::Hardware
+            | MenuAction::PlayAgain
+            | MenuAction::Retry => {
+                // Playing again keeps the current mode
+                match event {
+                    MenuAction::Play => *game_mode = GameMode::Classic,
+                    MenuAction::Practice => *game_mode = GameMode::Practice,
+                    MenuAction::Zen => *game_mode = GameMode::Zen,
+                    MenuAction::Hardware => *game_mode = GameMode::Hardware,
+                    _ => {}
+                }
+                progress.0 = 0;
+                score.current = 0;
+                score.new_best = false;
+                run_time.0.reset();
+                continues.used = 0;
+                milestones.reached = 0;
+                *lives = Lives::default();
+                peeks.0 = settings.peeks;
+                // `update_pattern` fills the pattern up to the starting level
+                pattern.0.clear();
+                boosted_start.0 = settings.starting_level > 1;
+                if *event == MenuAction::Retry {
+                    boosted_start.0 = false;
+                    pattern.0 = failed_pattern.0.clone();
+                    commands.insert_resource(RepeatPattern);
+                }
+                commands.insert_resource(NextState(AppState::Playing));
+                commands.insert_resource(NextState(SimonState::MonkeySee));
+            }
+            MenuAction::Continue => {
+                if continues.available(&score) {
+                    score.current -= continues.cost;
+                    continues.used += 1;
+                    progress.0 = 0;
+                    commands.insert_resource(RepeatPattern);
+                    commands.insert_resource(NextState(AppState::Playing));
+                    commands.insert_resource(NextState(SimonState::MonkeySee));
+                }
+            }
+            MenuAction::Settings => {
+                commands.insert_resource(SettingsReturn(state.0));
+                commands.insert_resource(NextState(AppState::Settings));
+            }
+            MenuAction::Stats => {
+                commands.insert_resource(NextState(AppState::Stats));
+            }
+            MenuAction::Leaderboard => {
+                commands.insert_resource(NextState(AppState::Leaderboard));
+            }
+            MenuAction::ShowLeaderboard(tab) => {
+                // Entering the same state again respawns the screen for the new tab
+                commands.insert_resource(*tab);
+                commands.insert_resource(NextState(AppState::Leaderboard));
+            }
+            MenuAction::ShowSettings(page) => {
+                // Like the leaderboard tabs, entering the same state again respawns the screen
+                commands.insert_resource(*page);
+                commands.insert_resource(NextState(AppState::Settings));
+            }
+            MenuAction::Composer => {
+                commands.insert_resource(NextState(AppState::Composer));
+            }
+            MenuAction::MainMenu => leave_game(&mut commands, AppState::Menu),
+            MenuAction::Back if matches!(state.0, AppState::Stats | AppState::Leaderboard) => {
+                commands.insert_resource(NextState(AppState::Menu));
+            }
+            MenuAction::Back => {
+                let previous = settings_return
+                    .as_ref()
+                    .map_or(AppState::Menu, |settings_return| settings_return.0);
+                commands.insert_resource(NextState(previous));
+            }
+            MenuAction::Change(setting) => setting.cycle(&mut settings),
+            // Handled by the dialog handlers, `pause_handler`, `start_ghost`,
+            // `start_challenge` and `copy_result`
+            MenuAction::WatchBestRun
+            | MenuAction::Challenge
+            | MenuAction::SeedPrompt
+            | MenuAction::ConfirmSeed
+            | MenuAction::CancelSeed
+            | MenuAction::RerollSeed
+            | MenuAction::CopyResult
+            | MenuAction::QuitPrompt
+            | MenuAction::Quit
+            | MenuAction::CancelQuit
+            | MenuAction::ResetPrompt
+            | MenuAction::ResetProgress
+            | MenuAction::CancelReset
+            | MenuAction::Pause
+            | MenuAction::Resume => {}
+        }
+    }
+}
+
+/// Keyboard shortcuts for menus: Escape to go back, Y/N in the quit dialog
+fn menu_shortcuts(
+    keys: Res<Input<KeyCode>>,
+    app_state: Res<CurrentState<AppState>>,
+    state: Res<CurrentState<SimonState>>,
+    quit_dialogs: Query<(), With<QuitDialog>>,
+    reset_dialogs: Query<(), With<ResetDialog>>,
+    seed_dialogs: Query<(), With<SeedDialog>>,
+    mut menu_event_writer: EventWriter<MenuAction>,
+) {
+    let dialog_open = !quit_dialogs.is_empty();
+    let seed_open = !seed_dialogs.is_empty();
+    let reset_open = !reset_dialogs.is_empty();
+    if dialog_open {
+        if keys.just_pressed(KeyCode::Y) {
+            menu_event_writer.send(MenuAction::Quit);
+        } else if keys.just_pressed(KeyCode::N) {
+            menu_event_writer.send(MenuAction::CancelQuit);
+        }
+    }
+
+    if keys.just_pressed(KeyCode::Escape) {
+        let action = match app_state.0 {
+            AppState::Loading => return,
+            AppState::Menu if dialog_open => MenuAction::CancelQuit,
+            AppState::Menu if seed_open => MenuAction::CancelSeed,
+            AppState::Menu => MenuAction::QuitPrompt,
+            AppState::Paused => MenuAction::Resume,
+            // Any key skips the review
+            AppState::Playing if state.0 == SimonState::Review => return,
+            AppState::Playing => MenuAction::Pause,
+            AppState::GameOver | AppState::Victory | AppState::Composer => MenuAction::MainMenu,
+            AppState::Settings if reset_open => MenuAction::CancelReset,
+            AppState::Settings | AppState::Stats | AppState::Leaderboard => MenuAction::Back,
+        };
+        menu_event_writer.send(action);
+    }
+}
+
+/// Opens, closes and confirms the quit dialog
+fn quit_dialog_handler(
+    mut commands: Commands,
+    mut event_reader: EventReader<MenuAction>,
+    mut app_exit_writer: EventWriter<AppExit>,
+    mut main_menus: Query<&mut Visibility, With<MainMenuScreen>>,
+    quit_dialogs: Query<Entity, With<QuitDialog>>,
+    font: Res<UiFont>,
+    settings: Res<Settings>,
+) {
+    for event in event_reader.iter() {
+        match event {
+            MenuAction::QuitPrompt if quit_dialogs.is_empty() => {
+                for mut visibility in main_menus.iter_mut() {
+                    visibility.is_visible = false;
+                }
+                spawn_dialog(
+                    &mut commands,
+                    QuitDialog,
+                    &font,
+                    settings.ui_text_color.color(),
+                    "Quit?",
+                    [
+                        (MenuAction::Quit, "Yes (Y)"),
+                        (MenuAction::CancelQuit, "No (N)"),
+                    ],
+                );
+            }
+            MenuAction::CancelQuit => {
+                for entity in quit_dialogs.iter() {
+                    commands.entity(entity).despawn_recursive();
+                }
+                for mut visibility in main_menus.iter_mut() {
+                    visibility.is_visible = true;
+                }
+            }
+            MenuAction::Quit => app_exit_writer.send(AppExit),
+            _ => {}
+        }
+    }
+}
+
+/// Opens and closes the reset progress dialog, and wipes the high score,
+/// stats and best run once confirmed
+#[allow(clippy::too_many_arguments)]
+fn reset_dialog_handler(
+    mut commands: Commands,
+    mut event_reader: EventReader<MenuAction>,
+    mut settings_screens: Query<&mut Visibility, With<SettingsScreen>>,
+    reset_dialogs: Query<Entity, With<ResetDialog>>,
+    mut score: ResMut<Score>,
+    mut stats: ResMut<Stats>,
+    mut save_data: ResMut<SaveData>,
+    font: Res<UiFont>,
+    settings: Res<Settings>,
+) {
+    for event in event_reader.iter() {
+        match event {
+            MenuAction::ResetPrompt if reset_dialogs.is_empty() => {
+                for mut visibility in settings_screens.iter_mut() {
+                    visibility.is_visible = false;
+                }
+                spawn_dialog(
+                    &mut commands,
+                    ResetDialog,
+                    &font,
+                    settings.ui_text_color.color(),
+                    "Reset high score and stats?\nAre you sure?",
+                    [
+                        (MenuAction::ResetProgress, "Reset"),
+                        (MenuAction::CancelReset, "Cancel"),
+                    ],
+                );
+            }
+            MenuAction::ResetProgress | MenuAction::CancelReset => {
+                if *event == MenuAction::ResetProgress {
+                    score.high = 0;
+                    *stats = Stats::default();
+                    *save_data = SaveData::default();
+                }
+                for entity in reset_dialogs.iter() {
+                    commands.entity(entity).despawn_recursive();
+                }
+                for mut visibility in settings_screens.iter_mut() {
+                    visibility.is_visible = true;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Spawns a dialog with a question and a button for each answer
+fn spawn_dialog<const N: usize>(
+    commands: &mut Commands,
+    marker: impl Component,
+    font: &UiFont,
+    text_color: Color,
+    question: &str,
+    answers: [(MenuAction, &str); N],
+) {
+    let label_style = menu_text_style(font, 28.0, text_color);
+    commands
+        .spawn(fullscreen_column())
+        .insert(marker)
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        padding: UiRect::all(Val::Px(16.0)),
+                        ..Default::default()
+                    },
+                    background_color: Color::rgb(0.05, 0.05, 0.05).into(),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(
+                        TextBundle::from_section(question, label_style.clone())
+                            .with_text_alignment(TextAlignment::CENTER),
+                    );
+                    for (action, label) in answers {
+                        spawn_menu_button(parent, action, label, &label_style);
+                    }
+                });
+        });
+}
+
+/// Pauses rounds when the window loses focus, resuming on focus if enabled
+fn pause_on_focus_loss(
+    mut focus_events: EventReader<WindowFocused>,
+    mut menu_event_writer: EventWriter<MenuAction>,
+    app_state: Res<CurrentState<AppState>>,
+    state: Res<CurrentState<SimonState>>,
+    settings: Res<Settings>,
+    mut auto_paused: Local<bool>,
+) {
+    for event in focus_events.iter() {
+        let in_round = matches!(state.0, SimonState::MonkeySee | SimonState::MonkeyDo);
+        if !event.focused && in_round && app_state.0 == AppState::Playing {
+            *auto_paused = true;
+            menu_event_writer.send(MenuAction::Pause);
+        } else if event.focused && *auto_paused {
+            *auto_paused = false;
+            // Only resume a pause this started, in case the player left it some other way
+            if settings.auto_resume && app_state.0 == AppState::Paused {
+                menu_event_writer.send(MenuAction::Resume);
+            }
+        }
+    }
+}
+
+/// Pauses and resumes the game
+fn pause_handler(
+    mut commands: Commands,
+    mut event_reader: EventReader<MenuAction>,
+    state: Res<CurrentState<AppState>>,
+) {
+    for event in event_reader.iter() {
+        match event {
+            MenuAction::Pause if state.0 == AppState::Playing => {
+                commands.insert_resource(NextState(AppState::Paused));
+            }
+            MenuAction::Resume if state.0 == AppState::Paused => {
+                commands.insert_resource(NextState(AppState::Playing));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Stops the demo timing and shows the pause screen
+fn spawn_pause_screen(
+    mut commands: Commands,
+    mut timesteps: ResMut<FixedTimesteps>,
+    font: Res<UiFont>,
+    settings: Res<Settings>,
+) {
+    if let Some(timestep) = timesteps.get_mut(FixedUpdate.as_str()) {
+        timestep.pause();
+    }
+    let title_style = menu_text_style(&font, 64.0, settings.ui_text_color.color());
+    let label_style = menu_text_style(&font, 28.0, settings.ui_text_color.color());
+    commands
+        .spawn(fullscreen_column())
+        .insert(PauseScreen)
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("Paused", title_style));
+            spawn_menu_button(parent, MenuAction::Resume, "Resume", &label_style);
+            spawn_menu_button(parent, MenuAction::MainMenu, "Main Menu", &label_style);
+        });
+}
+
+/// Restarts the demo timing when leaving the pause screen
+fn unpause_fixed_timestep(mut timesteps: ResMut<FixedTimesteps>) {
+    if let Some(timestep) = timesteps.get_mut(FixedUpdate.as_str()) {
+        timestep.unpause();
+    }
+}
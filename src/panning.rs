@@ -0,0 +1,126 @@
+use bevy::{
+    audio::{play_queued_audio_system, AudioOutput, Decodable, Sample, Source},
+    prelude::*,
+    reflect::TypeUuid,
+};
+
+/// Stereo panning for the button sounds
+pub struct PanningPlugin;
+
+impl Plugin for PanningPlugin {
+    fn build(&self, app: &mut App) {
+        // Panned sounds are decoded through a wrapper, so they get their own audio output
+        app.init_non_send_resource::<AudioOutput<PannedSound>>()
+            .add_asset::<PannedSound>()
+            .init_resource::<Audio<PannedSound>>()
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                play_queued_audio_system::<PannedSound>,
+            );
+    }
+}
+
+/// How far from the middle a button has to be to be panned the most
+const PAN_WIDTH: f32 = 0.12;
+/// How much the quieter side is turned down for the furthest buttons
+const PAN_STRENGTH: f32 = 0.6;
+
+/// A sound played louder on one side
+#[derive(TypeUuid)]
+#[uuid = "c32fd2b7-fa35-472d-8ccb-446419406376"]
+pub struct PannedSound {
+    source: AudioSource,
+    /// From -1 for fully left to 1 for fully right
+    pan: f32,
+}
+
+impl PannedSound {
+    /// `source` panned towards `x` on the desk
+    pub fn new(source: AudioSource, x: f32) -> Self {
+        Self {
+            source,
+            pan: (x / PAN_WIDTH).clamp(-1.0, 1.0) * PAN_STRENGTH,
+        }
+    }
+
+    /// The volume of the left and right channels
+    fn gains(&self) -> [f32; 2] {
+        [1.0 - self.pan.max(0.0), 1.0 + self.pan.min(0.0)]
+    }
+}
+
+type SourceDecoder = <AudioSource as Decodable>::Decoder;
+type SourceSample = <AudioSource as Decodable>::DecoderItem;
+
+/// Decodes the source, turning each channel up or down
+///
+/// Mono sources are spread across both channels so they can be panned
+pub struct PannedDecoder {
+    inner: SourceDecoder,
+    gains: [f32; 2],
+    mono: bool,
+    /// The next channel to be output
+    channel: u16,
+    /// The right half of a spread mono sample
+    pending: Option<SourceSample>,
+}
+
+impl Iterator for PannedDecoder {
+    type Item = SourceSample;
+
+    fn next(&mut self) -> Option<SourceSample> {
+        if let Some(sample) = self.pending.take() {
+            return Some(sample);
+        }
+        let sample = self.inner.next()?;
+        if self.mono {
+            self.pending = Some(sample.amplify(self.gains[1]));
+            return Some(sample.amplify(self.gains[0]));
+        }
+
+        let channel = self.channel;
+        self.channel = (channel + 1) % self.inner.channels();
+        Some(match self.gains.get(channel as usize) {
+            Some(gain) => sample.amplify(*gain),
+            None => sample,
+        })
+    }
+}
+
+impl Source for PannedDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        if self.mono {
+            2
+        } else {
+            self.inner.channels()
+        }
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<std::time::Duration> {
+        self.inner.total_duration()
+    }
+}
+
+impl Decodable for PannedSound {
+    type Decoder = PannedDecoder;
+    type DecoderItem = SourceSample;
+
+    fn decoder(&self) -> Self::Decoder {
+        let inner = self.source.decoder();
+        PannedDecoder {
+            mono: inner.channels() == 1,
+            inner,
+            gains: self.gains(),
+            channel: 0,
+            pending: None,
+        }
+    }
+}
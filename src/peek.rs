@@ -0,0 +1,67 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{
+    preview::PatternPreview, settings::Settings, AppState, ButtonEvent, Pattern, Progress,
+    SimonState,
+};
+
+/// Lets the player light up the next button a few times a run
+pub struct PeekPlugin;
+
+impl Plugin for PeekPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Peeks>()
+            .init_resource::<PeekedThisRound>()
+            .add_enter_system(SimonState::MonkeyDo, clear_peeked)
+            .add_system(
+                peek.run_in_state(AppState::Playing)
+                    .run_in_state(SimonState::MonkeyDo)
+                    .run_unless_resource_exists::<PatternPreview>(),
+            );
+    }
+}
+
+/// The key that uses a peek
+pub const PEEK_KEY: KeyCode = KeyCode::P;
+const PEEK_GAMEPAD_BUTTON: GamepadButtonType = GamepadButtonType::Select;
+
+/// Peeks left this run, refilled from the setting when a run starts
+#[derive(Resource, Default)]
+pub struct Peeks(pub usize);
+
+/// Whether a peek was used this round, which costs the round its points
+#[derive(Resource, Default)]
+pub struct PeekedThisRound(pub bool);
+
+/// Starts each round without a peek
+fn clear_peeked(mut peeked: ResMut<PeekedThisRound>) {
+    peeked.0 = false;
+}
+
+/// Lights up the next button to press when a peek is used
+#[allow(clippy::too_many_arguments)]
+fn peek(
+    mut peeks: ResMut<Peeks>,
+    mut peeked: ResMut<PeekedThisRound>,
+    mut button_event_writer: EventWriter<ButtonEvent>,
+    keys: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    pattern: Res<Pattern>,
+    progress: Res<Progress>,
+    settings: Res<Settings>,
+) {
+    let pressed = keys.just_pressed(PEEK_KEY)
+        || gamepads.iter().any(|gamepad| {
+            gamepad_buttons.just_pressed(GamepadButton::new(gamepad, PEEK_GAMEPAD_BUTTON))
+        });
+    if !pressed || peeks.0 == 0 {
+        return;
+    }
+    if let Some(button) = pattern.expected(progress.0, &settings) {
+        peeks.0 -= 1;
+        peeked.0 = true;
+        button_event_writer.send(ButtonEvent::Lit(button));
+    }
+}
@@ -0,0 +1,128 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{
+    fonts::UiFont,
+    settings::{ButtonPalette, Settings},
+    AppState, Pattern, SimonState,
+};
+
+/// An assist that shows the pattern as a row of dots before it has to be repeated
+pub struct PreviewPlugin;
+
+impl Plugin for PreviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_enter_system(SimonState::MonkeyDo, start_preview)
+            .add_exit_system(SimonState::MonkeyDo, end_preview)
+            .add_system(
+                hide_preview
+                    .run_in_state(AppState::Playing)
+                    .run_in_state(SimonState::MonkeyDo)
+                    .run_if_resource_exists::<PatternPreview>(),
+            );
+    }
+}
+
+const PREVIEW_DOT_SIZE: f32 = 24.0;
+
+/// Shown while the pattern preview is up, presses aren't accepted until it's gone
+#[derive(Resource)]
+pub struct PatternPreview(Timer);
+
+/// Marker component for the preview's dots
+#[derive(Component)]
+struct PreviewScreen;
+
+/// Shows the pattern as dots, if the assist is on
+fn start_preview(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    pattern: Res<Pattern>,
+    palette: Res<ButtonPalette>,
+    font: Res<UiFont>,
+) {
+    let Some(seconds) = settings.pattern_preview else {
+        return;
+    };
+    commands.insert_resource(PatternPreview(Timer::from_seconds(
+        seconds,
+        TimerMode::Once,
+    )));
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Percent(12.0),
+                    ..Default::default()
+                },
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            background_color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .insert(PreviewScreen)
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Assist: Pattern Preview",
+                TextStyle {
+                    font: font.handle.clone(),
+                    font_size: 24.0,
+                    color: settings.ui_text_color.color(),
+                },
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_wrap: FlexWrap::Wrap,
+                        justify_content: JustifyContent::Center,
+                        max_size: Size::new(Val::Percent(80.0), Val::Auto),
+                        ..Default::default()
+                    },
+                    background_color: Color::NONE.into(),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    for button in pattern.0.iter() {
+                        parent.spawn(NodeBundle {
+                            style: Style {
+                                size: Size::new(
+                                    Val::Px(PREVIEW_DOT_SIZE),
+                                    Val::Px(PREVIEW_DOT_SIZE),
+                                ),
+                                margin: UiRect::all(Val::Px(4.0)),
+                                ..Default::default()
+                            },
+                            background_color: palette.color(*button).into(),
+                            ..Default::default()
+                        });
+                    }
+                });
+        });
+}
+
+/// Hides the preview once its time is up
+fn hide_preview(
+    mut commands: Commands,
+    mut preview: ResMut<PatternPreview>,
+    screens: Query<Entity, With<PreviewScreen>>,
+    time: Res<Time>,
+) {
+    if preview.0.tick(time.delta()).finished() {
+        commands.remove_resource::<PatternPreview>();
+        for entity in screens.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Clears the preview if the round ends early
+fn end_preview(mut commands: Commands, screens: Query<Entity, With<PreviewScreen>>) {
+    commands.remove_resource::<PatternPreview>();
+    for entity in screens.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
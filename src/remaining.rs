@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{
+    fonts::{ThemedText, UiFont},
+    settings::Settings,
+    AppState, Pattern, Progress, SimonState,
+};
+
+/// Counts down the presses left while repeating the pattern, a lighter
+/// alternative to the pattern preview
+pub struct RemainingPlugin;
+
+impl Plugin for RemainingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup_remaining_label)
+            .add_system(update_remaining_label);
+    }
+}
+
+/// Marker component for the presses left label
+#[derive(Component)]
+struct RemainingLabel;
+
+/// Spawns the label along the top of the screen, hidden until it's needed
+fn setup_remaining_label(mut commands: Commands, font: Res<UiFont>, settings: Res<Settings>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Percent(2.0),
+                    ..Default::default()
+                },
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                justify_content: JustifyContent::Center,
+                ..Default::default()
+            },
+            background_color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: font.handle.clone(),
+                            font_size: 36.0,
+                            color: settings.ui_text_color.color(),
+                        },
+                    ),
+                    visibility: Visibility::INVISIBLE,
+                    ..Default::default()
+                })
+                .insert((RemainingLabel, ThemedText));
+        });
+}
+
+/// Shows how many presses are left while the player repeats the pattern,
+/// if the setting is on, hiding it while paused
+fn update_remaining_label(
+    app_state: Res<CurrentState<AppState>>,
+    state: Res<CurrentState<SimonState>>,
+    pattern: Res<Pattern>,
+    progress: Res<Progress>,
+    settings: Res<Settings>,
+    mut labels: Query<(&mut Text, &mut Visibility), With<RemainingLabel>>,
+) {
+    if !app_state.is_changed()
+        && !state.is_changed()
+        && !pattern.is_changed()
+        && !progress.is_changed()
+        && !settings.is_changed()
+    {
+        return;
+    }
+    let shown = settings.remaining_presses
+        && app_state.0 == AppState::Playing
+        && state.0 == SimonState::MonkeyDo
+        && !pattern.0.is_empty();
+    let remaining = pattern.0.len().saturating_sub(progress.0);
+    for (mut text, mut visibility) in labels.iter_mut() {
+        visibility.is_visible = shown;
+        if shown {
+            text.sections[0].value = format!("{remaining} left");
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{menu::MenuAction, settings::Settings, toast::Toast, Pattern, SimonState};
+
+/// Warns the player when the pattern gets long enough to be repeated backwards
+pub struct ReversePlugin;
+
+impl Plugin for ReversePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReversalAnnounced>()
+            .add_enter_system(SimonState::MonkeyDo, announce_reversal)
+            .add_system(reset_reversal_announced);
+    }
+}
+
+/// The warning stays up longer than most toasts so it isn't missed
+const REVERSAL_TOAST_SECONDS: f32 = 5.0;
+
+/// Whether this run has already warned about the reversal
+#[derive(Resource, Default)]
+struct ReversalAnnounced(bool);
+
+/// Warns the player the first time they have to repeat a pattern backwards
+fn announce_reversal(
+    mut announced: ResMut<ReversalAnnounced>,
+    mut toast_writer: EventWriter<Toast>,
+    pattern: Res<Pattern>,
+    settings: Res<Settings>,
+) {
+    if announced.0 || !settings.reverses_input(pattern.0.len()) {
+        return;
+    }
+    announced.0 = true;
+    toast_writer.send(Toast {
+        text: "Reversed! Repeat the pattern backwards".into(),
+        seconds: REVERSAL_TOAST_SECONDS,
+    });
+}
+
+/// Warns again in the next run, continuing keeps the run going
+fn reset_reversal_announced(
+    mut announced: ResMut<ReversalAnnounced>,
+    mut menu_events: EventReader<MenuAction>,
+) {
+    let new_run = menu_events.iter().any(|event| {
+        matches!(
+            event,
+            MenuAction::Play
+                | MenuAction::Practice
+                | MenuAction::Zen
+                | MenuAction::Hardware
+                | MenuAction::PlayAgain
+                | MenuAction::Retry
+        )
+    });
+    if new_run {
+        announced.0 = false;
+    }
+}
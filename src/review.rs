@@ -0,0 +1,157 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{
+    leave_game, settings::Settings, AppState, Button, ButtonEvent, Pattern, SimonState, WrongPress,
+};
+
+/// Shows the player where they went wrong before the game over screen
+pub struct ReviewPlugin;
+
+impl Plugin for ReviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputHistory>()
+            .add_enter_system(SimonState::MonkeyDo, clear_input_history)
+            .add_enter_system(SimonState::Review, start_review)
+            .add_exit_system(SimonState::Review, end_review)
+            .add_system(
+                play_review
+                    .run_in_state(AppState::Playing)
+                    .run_in_state(SimonState::Review),
+            )
+            .add_system(
+                skip_review
+                    .run_in_state(AppState::Playing)
+                    .run_in_state(SimonState::Review),
+            );
+    }
+}
+
+/// Time between each step of the review
+const REVIEW_BEAT_SECONDS: f32 = 0.5;
+/// How long the mistake stays up before the game over screen
+const REVIEW_HOLD_SECONDS: f32 = 1.5;
+
+/// Buttons pressed this round, recorded by `validate_buttons`
+#[derive(Resource, Default)]
+pub struct InputHistory(pub Vec<Button>);
+
+/// What's left to show in the review
+#[derive(Resource)]
+struct Review {
+    steps: VecDeque<ReviewStep>,
+    beat: Timer,
+}
+
+#[derive(Clone, Copy)]
+enum ReviewStep {
+    /// Light a button the player got right
+    Correct(Button),
+    /// Light the button they should have pressed and shake the one they did
+    Mistake { expected: Button, pressed: Button },
+    /// Light the button they ran out of time to press
+    Missed(Button),
+    /// Wait before moving on
+    Hold,
+}
+
+/// Starts each round with an empty history
+fn clear_input_history(mut history: ResMut<InputHistory>) {
+    history.0.clear();
+}
+
+/// Builds the review from the pattern and what was pressed
+fn start_review(
+    mut commands: Commands,
+    history: Res<InputHistory>,
+    pattern: Res<Pattern>,
+    settings: Res<Settings>,
+) {
+    let mut steps = VecDeque::new();
+    let expected = |index| pattern.expected(index, &settings);
+    // Every press was right if the turn timer ran out
+    let all_correct = history
+        .0
+        .iter()
+        .enumerate()
+        .all(|(index, button)| expected(index) == Some(*button));
+    if all_correct {
+        steps.extend(history.0.iter().copied().map(ReviewStep::Correct));
+        if let Some(expected) = expected(history.0.len()) {
+            steps.push_back(ReviewStep::Missed(expected));
+        }
+    } else if let Some((pressed, correct)) = history.0.split_last() {
+        steps.extend(correct.iter().copied().map(ReviewStep::Correct));
+        if let Some(expected) = expected(correct.len()) {
+            steps.push_back(ReviewStep::Mistake {
+                expected,
+                pressed: *pressed,
+            });
+        }
+    }
+    steps.push_back(ReviewStep::Hold);
+
+    commands.insert_resource(Review {
+        steps,
+        beat: Timer::from_seconds(REVIEW_BEAT_SECONDS, TimerMode::Once),
+    });
+}
+
+/// Steps through the review, then shows the game over screen
+fn play_review(
+    mut commands: Commands,
+    mut review: ResMut<Review>,
+    mut button_event_writer: EventWriter<ButtonEvent>,
+    mut wrong_press_writer: EventWriter<WrongPress>,
+    time: Res<Time>,
+) {
+    if !review.beat.tick(time.delta()).finished() {
+        return;
+    }
+    let Some(step) = review.steps.pop_front() else {
+        leave_game(&mut commands, AppState::GameOver);
+        return;
+    };
+
+    let seconds = match step {
+        ReviewStep::Correct(button) => {
+            button_event_writer.send(ButtonEvent::Lit(button));
+            REVIEW_BEAT_SECONDS
+        }
+        ReviewStep::Mistake { expected, pressed } => {
+            button_event_writer.send(ButtonEvent::Lit(expected));
+            wrong_press_writer.send(WrongPress(pressed));
+            REVIEW_BEAT_SECONDS
+        }
+        ReviewStep::Missed(expected) => {
+            button_event_writer.send(ButtonEvent::Lit(expected));
+            REVIEW_BEAT_SECONDS
+        }
+        ReviewStep::Hold => REVIEW_HOLD_SECONDS,
+    };
+    review.beat = Timer::from_seconds(seconds, TimerMode::Once);
+}
+
+/// Throws away whatever is left of the review
+fn end_review(mut commands: Commands) {
+    commands.remove_resource::<Review>();
+}
+
+/// Jumps to the game over screen on any input
+fn skip_review(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    touches: Res<Touches>,
+) {
+    if keys.get_just_pressed().next().is_some()
+        || mouse_buttons.get_just_pressed().next().is_some()
+        || gamepad_buttons.get_just_pressed().next().is_some()
+        || touches.iter_just_pressed().next().is_some()
+    {
+        leave_game(&mut commands, AppState::GameOver);
+    }
+}
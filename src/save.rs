@@ -0,0 +1,252 @@
+use std::{collections::BTreeMap, fs};
+
+use bevy::{app::AppExit, prelude::*};
+use iyes_loopless::prelude::*;
+use ron::ser::PrettyConfig;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    challenge::Challenge, ghost::BestRun, menu::MenuAction, settings::Settings, AppState,
+    BoostedStart, GameMode, Pattern, RunTime, Score, SimonState,
+};
+
+/// Loads and saves progress, like the high score and lifetime stats
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        let save_data: SaveData = read_ron(SAVE_PATH);
+        app.insert_resource(Score {
+            current: 0,
+            high: save_data.high_score,
+            new_best: false,
+        })
+        .insert_resource(save_data.stats.clone())
+        .insert_resource(save_data)
+        .add_system(record_high_score)
+        .add_system(record_stats)
+        .add_enter_system(AppState::GameOver, hold_final_score)
+        .add_system(record_final_score.run_in_state(AppState::GameOver))
+        .add_enter_system(AppState::Victory, record_victory)
+        .add_system(save_progress)
+        .add_system_to_stage(CoreStage::Last, save_progress_on_exit);
+    }
+}
+
+const SAVE_PATH: &str = "save.ron";
+/// Entries kept on each leaderboard
+const LEADERBOARD_SIZE: usize = 5;
+
+/// Progress that persists between launches
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct SaveData {
+    pub high_score: usize,
+    pub stats: Stats,
+    /// Replayed from the main menu
+    pub best_run: BestRun,
+    pub leaderboards: Leaderboards,
+}
+
+/// The best ranked classic runs, kept apart for each kind of record
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct Leaderboards {
+    /// Highest final scores, best first
+    pub high_scores: Vec<usize>,
+    /// Fastest wins in seconds, best first, for each max pattern length
+    pub fastest_times: BTreeMap<usize, Vec<f32>>,
+}
+
+impl Leaderboards {
+    /// Adds a final score if it makes the board
+    fn add_score(&mut self, score: usize) {
+        if score > 0 {
+            insert_ranked(&mut self.high_scores, score, |new, old| new > old);
+        }
+    }
+
+    /// Adds the time taken to win with patterns of length `target`
+    fn add_time(&mut self, target: usize, seconds: f32) {
+        let times = self.fastest_times.entry(target).or_default();
+        insert_ranked(times, seconds, |new, old| new < old);
+    }
+}
+
+/// Inserts `value` before the first entry it beats, and drops whatever
+/// falls off the end of the board
+fn insert_ranked<T>(entries: &mut Vec<T>, value: T, beats: impl Fn(&T, &T) -> bool) {
+    let index = entries
+        .iter()
+        .position(|entry| beats(&value, entry))
+        .unwrap_or(entries.len());
+    entries.insert(index, value);
+    entries.truncate(LEADERBOARD_SIZE);
+}
+
+/// A game over score waiting to go on the leaderboard, held back in case the
+/// run is continued
+#[derive(Resource)]
+struct PendingScore(usize);
+
+/// Lifetime stats across every session
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct Stats {
+    /// Runs that ended in a game over
+    pub games_played: usize,
+    /// Buttons pressed while repeating a pattern
+    pub buttons_pressed: usize,
+    /// The longest pattern ever completed
+    pub longest_pattern: usize,
+    /// Wrong presses, including in practice
+    pub failures: usize,
+}
+
+/// Reads a RON file, falling back to the default if it's missing or broken
+pub fn read_ron<T: DeserializeOwned + Default>(path: &str) -> T {
+    match fs::read_to_string(path) {
+        Ok(contents) => ron::from_str(&contents).unwrap_or_else(|error| {
+            warn!("Couldn't parse {path}, using defaults: {error}");
+            T::default()
+        }),
+        Err(_) => T::default(),
+    }
+}
+
+/// Writes a RON file, warning if it fails
+///
+/// The file is written next to `path` and then moved over it, so quitting
+/// partway through leaves the old file rather than half of the new one
+pub fn write_ron<T: Serialize>(path: &str, value: &T) {
+    let temp_path = format!("{path}.tmp");
+    let result = ron::ser::to_string_pretty(value, PrettyConfig::default())
+        .map_err(|error| error.to_string())
+        .and_then(|contents| fs::write(&temp_path, contents).map_err(|error| error.to_string()))
+        .and_then(|()| fs::rename(&temp_path, path).map_err(|error| error.to_string()));
+    if let Err(error) = result {
+        warn!("Couldn't save {path}: {error}");
+    }
+}
+
+/// Copies a new high score into the `SaveData`
+fn record_high_score(score: Res<Score>, mut save_data: ResMut<SaveData>) {
+    if score.is_changed() && score.high > save_data.high_score {
+        save_data.high_score = score.high;
+    }
+}
+
+/// Copies the `Stats` into the `SaveData` whenever the round moves on
+///
+/// Stats change on every press, so waiting for the round to end keeps the save
+/// from being written in the middle of input
+fn record_stats(
+    stats: Res<Stats>,
+    state: Res<CurrentState<SimonState>>,
+    mut save_data: ResMut<SaveData>,
+) {
+    if state.is_changed() && save_data.stats != *stats {
+        save_data.stats = stats.clone();
+    }
+}
+
+/// Whether the run counts for the leaderboards, like for the high score
+fn ranked_run(
+    game_mode: &GameMode,
+    boosted_start: &BoostedStart,
+    settings: &Settings,
+    challenge: Option<&Challenge>,
+) -> bool {
+    *game_mode == GameMode::Classic
+        && !boosted_start.0
+        && !settings.preview_unranked()
+        && challenge.is_none()
+}
+
+/// Holds on to a ranked run's score when it ends, until it's known whether
+/// the run is continued
+fn hold_final_score(
+    mut commands: Commands,
+    score: Res<Score>,
+    game_mode: Res<GameMode>,
+    boosted_start: Res<BoostedStart>,
+    settings: Res<Settings>,
+    challenge: Option<Res<Challenge>>,
+) {
+    if ranked_run(&game_mode, &boosted_start, &settings, challenge.as_deref()) {
+        commands.insert_resource(PendingScore(score.current));
+    }
+}
+
+/// Puts the held score on the leaderboard once the run is over for good, so a
+/// continued run is only counted once
+fn record_final_score(
+    mut commands: Commands,
+    mut event_reader: EventReader<MenuAction>,
+    mut save_data: ResMut<SaveData>,
+    pending: Option<Res<PendingScore>>,
+) {
+    let Some(pending) = pending else {
+        event_reader.clear();
+        return;
+    };
+    for event in event_reader.iter() {
+        match event {
+            MenuAction::PlayAgain | MenuAction::Retry | MenuAction::MainMenu => {
+                save_data.leaderboards.add_score(pending.0);
+                commands.remove_resource::<PendingScore>();
+            }
+            MenuAction::Continue => commands.remove_resource::<PendingScore>(),
+            _ => {}
+        }
+    }
+}
+
+/// Puts a winning run's score and time on the leaderboards
+#[allow(clippy::too_many_arguments)]
+fn record_victory(
+    mut save_data: ResMut<SaveData>,
+    score: Res<Score>,
+    pattern: Res<Pattern>,
+    run_time: Res<RunTime>,
+    game_mode: Res<GameMode>,
+    boosted_start: Res<BoostedStart>,
+    settings: Res<Settings>,
+    challenge: Option<Res<Challenge>>,
+) {
+    if !ranked_run(&game_mode, &boosted_start, &settings, challenge.as_deref()) {
+        return;
+    }
+    let leaderboards = &mut save_data.leaderboards;
+    leaderboards.add_score(score.current);
+    leaderboards.add_time(pattern.0.len(), run_time.0.elapsed_secs());
+}
+
+/// Saves progress whenever it changes
+fn save_progress(save_data: Res<SaveData>) {
+    if save_data.is_changed() && !save_data.is_added() {
+        write_ron(SAVE_PATH, &*save_data);
+    }
+}
+
+/// Saves progress one last time when the app quits
+///
+/// Runs last, so a high score or stats from the frame the app quit on aren't
+/// lost, and neither is a score still waiting on the game over screen
+fn save_progress_on_exit(
+    mut exit_reader: EventReader<AppExit>,
+    mut save_data: ResMut<SaveData>,
+    score: Res<Score>,
+    stats: Res<Stats>,
+    pending: Option<Res<PendingScore>>,
+) {
+    if exit_reader.iter().last().is_none() {
+        return;
+    }
+    save_data.high_score = save_data.high_score.max(score.high);
+    save_data.stats = stats.clone();
+    if let Some(pending) = pending {
+        save_data.leaderboards.add_score(pending.0);
+    }
+    write_ron(SAVE_PATH, &*save_data);
+}
@@ -0,0 +1,256 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{
+    fonts::{ThemedText, UiFont},
+    generator::{PatternGenerator, RandomGenerator, SeededGenerator},
+    menu::{fullscreen_column, menu_text_style, spawn_menu_button, MainMenuScreen, MenuAction},
+    settings::{ScoreboardCorner, Settings},
+    toast::Toast,
+    AppState, Pattern,
+};
+
+/// Shows the seed behind each run, and lets the player pick one
+pub struct SeedPlugin;
+
+impl Plugin for SeedPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup_seed_label)
+            .add_system(update_seed_label)
+            .add_system(seed_dialog_handler.run_in_state(AppState::Menu))
+            .add_system(type_seed.run_in_state(AppState::Menu));
+    }
+}
+
+const SEED_LABEL_SIZE: f32 = 18.0;
+const SEED_ERROR_COLOR: Color = Color::rgb(1.0, 0.4, 0.4);
+/// Digits in the biggest seed, `u64::MAX`
+const MAX_SEED_DIGITS: usize = 20;
+
+/// Marker component for the seed in the corner
+#[derive(Component)]
+struct SeedLabel;
+
+/// The seed entry dialog, and what's been typed into it
+#[derive(Component, Default)]
+pub struct SeedDialog {
+    typed: String,
+}
+
+/// Marker component for the text showing what's been typed
+#[derive(Component)]
+struct SeedInput;
+
+/// Marker component for the text saying what's wrong with the seed
+#[derive(Component)]
+struct SeedError;
+
+/// Spawns the seed label, hidden until a run starts
+fn setup_seed_label(mut commands: Commands, font: Res<UiFont>, settings: Res<Settings>) {
+    commands
+        .spawn(TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: font.handle.clone(),
+                    font_size: SEED_LABEL_SIZE,
+                    color: settings.ui_text_color.color(),
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            visibility: Visibility::INVISIBLE,
+            ..Default::default()
+        })
+        .insert((SeedLabel, ThemedText));
+}
+
+/// Where the seed goes, in the top left unless the scoreboard is there
+fn seed_label_position(scoreboard_corner: ScoreboardCorner) -> UiRect {
+    let corner = match scoreboard_corner {
+        ScoreboardCorner::TopLeft => ScoreboardCorner::BottomLeft,
+        _ => ScoreboardCorner::TopLeft,
+    };
+    corner.position()
+}
+
+/// Shows the seed that made the current pattern during and after a run
+///
+/// Challenges aren't generated from a seed, so they don't show one
+fn update_seed_label(
+    generator: Res<PatternGenerator>,
+    pattern: Res<Pattern>,
+    app_state: Res<CurrentState<AppState>>,
+    settings: Res<Settings>,
+    mut labels: Query<(&mut Text, &mut Style, &mut Visibility), With<SeedLabel>>,
+) {
+    if !generator.is_changed()
+        && !pattern.is_changed()
+        && !app_state.is_changed()
+        && !settings.is_changed()
+    {
+        return;
+    }
+    let in_run = matches!(
+        app_state.0,
+        AppState::Playing | AppState::Paused | AppState::GameOver | AppState::Victory
+    );
+    let seed = generator.0.seed().filter(|_| in_run);
+    let position = seed_label_position(settings.scoreboard_corner);
+    for (mut text, mut style, mut visibility) in labels.iter_mut() {
+        visibility.is_visible = seed.is_some();
+        if let Some(seed) = seed {
+            text.sections[0].value = format!("Seed {seed}");
+        }
+        if style.position != position {
+            style.position = position;
+        }
+    }
+}
+
+/// Opens, closes and confirms the seed dialog, and goes back to random seeds
+/// when re-rolled
+#[allow(clippy::too_many_arguments)]
+fn seed_dialog_handler(
+    mut commands: Commands,
+    mut event_reader: EventReader<MenuAction>,
+    mut toast_writer: EventWriter<Toast>,
+    mut main_menus: Query<&mut Visibility, With<MainMenuScreen>>,
+    seed_dialogs: Query<(Entity, &SeedDialog)>,
+    mut errors: Query<&mut Text, With<SeedError>>,
+    font: Res<UiFont>,
+    settings: Res<Settings>,
+) {
+    let mut close = false;
+    for event in event_reader.iter() {
+        match event {
+            MenuAction::SeedPrompt if seed_dialogs.is_empty() => {
+                for mut visibility in main_menus.iter_mut() {
+                    visibility.is_visible = false;
+                }
+                spawn_seed_dialog(&mut commands, &font, settings.ui_text_color.color());
+            }
+            MenuAction::ConfirmSeed => {
+                let Ok((_, dialog)) = seed_dialogs.get_single() else {
+                    continue;
+                };
+                match parse_seed(&dialog.typed) {
+                    Ok(seed) => {
+                        commands.insert_resource(PatternGenerator(Box::new(SeededGenerator::new(
+                            seed,
+                        ))));
+                        toast_writer.send(Toast::new(format!("Every run will use seed {seed}")));
+                        close = true;
+                    }
+                    Err(error) => {
+                        for mut text in errors.iter_mut() {
+                            text.sections[0].value = error.clone();
+                        }
+                    }
+                }
+            }
+            MenuAction::CancelSeed => close = true,
+            MenuAction::RerollSeed => {
+                commands.insert_resource(PatternGenerator(Box::new(RandomGenerator::default())));
+                toast_writer.send(Toast::new("Every run will use a new random seed"));
+            }
+            _ => {}
+        }
+    }
+    if close {
+        for (entity, _) in seed_dialogs.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        for mut visibility in main_menus.iter_mut() {
+            visibility.is_visible = true;
+        }
+    }
+}
+
+/// Reads a typed seed, saying what's wrong with it if it isn't one
+fn parse_seed(typed: &str) -> Result<u64, String> {
+    if typed.is_empty() {
+        return Err("Type a seed first".into());
+    }
+    typed
+        .parse()
+        .map_err(|_| format!("Seeds only go up to {}", u64::MAX))
+}
+
+/// Spawns the seed dialog over the main menu
+fn spawn_seed_dialog(commands: &mut Commands, font: &UiFont, text_color: Color) {
+    let label_style = menu_text_style(font, 28.0, text_color);
+    let error_style = TextStyle {
+        color: SEED_ERROR_COLOR,
+        ..menu_text_style(font, 20.0, text_color)
+    };
+    commands
+        .spawn(fullscreen_column())
+        .insert(SeedDialog::default())
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        padding: UiRect::all(Val::Px(16.0)),
+                        ..Default::default()
+                    },
+                    background_color: Color::rgb(0.05, 0.05, 0.05).into(),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Type a seed:",
+                        label_style.clone(),
+                    ));
+                    parent
+                        .spawn(TextBundle::from_section("_", label_style.clone()))
+                        .insert(SeedInput);
+                    parent
+                        .spawn(TextBundle::from_section("", error_style))
+                        .insert(SeedError);
+                    spawn_menu_button(parent, MenuAction::ConfirmSeed, "Use Seed", &label_style);
+                    spawn_menu_button(parent, MenuAction::CancelSeed, "Cancel", &label_style);
+                });
+        });
+}
+
+/// Types digits into the open seed dialog, with Backspace to delete
+fn type_seed(
+    mut characters: EventReader<ReceivedCharacter>,
+    keys: Res<Input<KeyCode>>,
+    mut seed_dialogs: Query<&mut SeedDialog>,
+    mut inputs: Query<&mut Text, (With<SeedInput>, Without<SeedError>)>,
+    mut errors: Query<&mut Text, (With<SeedError>, Without<SeedInput>)>,
+) {
+    let Ok(mut dialog) = seed_dialogs.get_single_mut() else {
+        characters.clear();
+        return;
+    };
+    let mut error = None;
+    for character in characters.iter() {
+        if character.char.is_ascii_digit() {
+            if dialog.typed.len() < MAX_SEED_DIGITS {
+                dialog.typed.push(character.char);
+            }
+        } else if !character.char.is_control() {
+            error = Some("Seeds are made of digits");
+        }
+    }
+    if keys.just_pressed(KeyCode::Back) {
+        dialog.typed.pop();
+    }
+    if !dialog.is_changed() && error.is_none() {
+        return;
+    }
+
+    for mut text in inputs.iter_mut() {
+        text.sections[0].value = format!("{}_", dialog.typed);
+    }
+    for mut text in errors.iter_mut() {
+        text.sections[0].value = error.unwrap_or_default().into();
+    }
+}
@@ -0,0 +1,1018 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use bevy::{app::AppExit, prelude::*, render::renderer::RenderAdapterInfo};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    save::{read_ron, write_ron},
+    Button,
+};
+
+/// Loads and saves the player's settings
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let first_launch = !Path::new(SETTINGS_PATH).exists();
+        app.insert_resource(Settings::load())
+            .insert_resource(ButtonPalette::load())
+            .add_system(save_settings)
+            .add_system_to_stage(CoreStage::Last, save_settings_on_exit);
+        if first_launch {
+            app.add_startup_system(detect_low_end_hardware);
+        }
+    }
+}
+
+const SETTINGS_PATH: &str = "settings.ron";
+const PALETTE_PATH: &str = "assets/palette.ron";
+
+/// Settings that persist between launches
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    /// Turn off animations, swapping states instantly instead
+    pub reduced_motion: bool,
+    /// Rumble gamepads on presses and failures
+    pub haptics: bool,
+    /// Multiplier for how brightly lit buttons glow
+    pub glow_strength: f32,
+    /// Let bright buttons bloom
+    pub bloom: bool,
+    /// Look straight down at the buttons instead of at an angle
+    pub flat_view: bool,
+    /// Let Space skip the rest of the pattern demo
+    pub skip_demo: bool,
+    /// Darker desk, a colored background and brighter buttons
+    pub high_contrast: bool,
+    /// How much of the pattern to show each round
+    pub demo_mode: DemoMode,
+    /// What the desk is made of
+    pub desk_theme: DeskTheme,
+    /// Keep presses made as the demo ends instead of ignoring them
+    pub buffer_input: bool,
+    /// The camera's vertical field of view, in degrees
+    pub fov: f32,
+    /// Give each button a different shape and size
+    pub varied_buttons: bool,
+    /// How fast the whole game goes
+    pub game_speed: GameSpeed,
+    /// Which set of button sounds to play
+    pub sound_pack: SoundPack,
+    /// Stretches the demo's glow and gaps for players who need more time,
+    /// on top of the `GameSpeed`
+    pub accessibility_timing: f32,
+    /// What happens to the pattern when a game would end
+    pub failure_policy: FailurePolicy,
+    /// Fade between showing the pattern and repeating it
+    pub round_wipe: bool,
+    /// Win once the pattern gets this long, or play forever
+    pub max_length: Option<usize>,
+    /// Pan button sounds towards where the buttons are
+    pub panned_audio: bool,
+    /// How long the pattern is in the first round
+    pub starting_level: usize,
+    /// How the lamp pulses on each beat of the demo
+    pub beat_pulse: BeatPulse,
+    /// Carry on when the window gets focus back after pausing for losing it
+    pub auto_resume: bool,
+    /// Count presses when the mouse is let go instead of when it's clicked
+    pub press_on_release: bool,
+    /// Show the pattern as dots for this many seconds before repeating it
+    pub pattern_preview: Option<f32>,
+    /// Let runs with the pattern preview set a high score
+    pub preview_ranked: bool,
+    /// Play the part of the demo already seen this many times faster
+    pub fast_forward: Option<f32>,
+    /// The color of menu and scoreboard text
+    pub ui_text_color: UiTextColor,
+    /// Remind players what to do if they sit still while repeating the pattern
+    pub idle_hint: bool,
+    /// Turn off shadows and bloom and cut down particles for weaker hardware
+    pub performance_mode: bool,
+    /// Multisample anti-aliasing samples, 1 turns it off
+    pub msaa_samples: u32,
+    /// Flip the buttons left to right, for left-handed players
+    pub mirror_layout: bool,
+    /// Show a dim drifting grid behind the desk
+    pub backdrop: bool,
+    /// How many times a run the next button can be lit up, for no points that round
+    pub peeks: usize,
+    /// Quietly play a button's sound when the cursor rests on it while repeating the pattern
+    pub hover_preview: bool,
+    /// Seconds allowed between presses before the round fails, or no limit
+    pub turn_timer: Option<f32>,
+    /// Never put the same button twice in a row in new patterns
+    pub no_repeats: bool,
+    /// How far the desk is tilted towards the camera, in degrees
+    pub desk_tilt: f32,
+    /// Pattern length after which the pattern has to be repeated backwards,
+    /// or never
+    pub reverse_at_level: Option<usize>,
+    /// Replay the pattern after a mistake instead of ending the run, unless
+    /// the last round was failed too
+    pub second_chance: bool,
+    /// Which corner of the screen the scoreboard sits in
+    pub scoreboard_corner: ScoreboardCorner,
+    /// Start each run with a short round that isn't scored
+    pub warm_up: bool,
+    /// How much the music drops while a button sounds, or not at all
+    pub music_ducking: Option<f32>,
+    /// Seconds the music takes to come back up after ducking
+    pub duck_release: f32,
+    /// Show a letter on each button, like R for red
+    pub button_letters: bool,
+    /// Speed up or slow down the demo and the turn timer to suit how the
+    /// player has been doing
+    pub adaptive_difficulty: bool,
+    /// Wrong presses ignored each round before it fails
+    pub forgiveness: usize,
+    /// Slowly circle the desk on the menu and game over screens, in degrees per second
+    pub camera_orbit: Option<f32>,
+    /// A mistake ends a classic hardware game, otherwise the pattern is played again
+    pub hardware_strict: bool,
+    /// Count down the presses left while repeating the pattern
+    pub remaining_presses: bool,
+}
+
+/// Scales the pace of the game, higher is faster
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct GameSpeed(pub f32);
+
+impl Default for GameSpeed {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+impl GameSpeed {
+    const MIN: f32 = 0.5;
+    const MAX: f32 = 2.0;
+
+    /// A duration in seconds at this speed
+    pub fn scale(self, seconds: f32) -> f32 {
+        seconds / self.0.clamp(Self::MIN, Self::MAX)
+    }
+}
+
+/// What the desk looks like
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DeskTheme {
+    #[default]
+    Plain,
+    Wood,
+    Metal,
+}
+
+/// A set of button sounds, each in its own directory
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SoundPack {
+    #[default]
+    Classic,
+    /// Not shipped yet, so it isn't offered and falls back to `Classic`
+    Piano,
+    /// Not shipped yet, so it isn't offered and falls back to `Classic`
+    Retro,
+}
+
+impl SoundPack {
+    /// Where the pack's sounds live, relative to the assets folder
+    pub fn directory(self) -> &'static str {
+        match self {
+            SoundPack::Classic => "sounds/buttons",
+            SoundPack::Piano => "sounds/buttons/piano",
+            SoundPack::Retro => "sounds/buttons/retro",
+        }
+    }
+}
+
+/// Colors for UI text, all light enough to read on either background
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UiTextColor {
+    #[default]
+    White,
+    Cream,
+    Yellow,
+    Cyan,
+}
+
+impl UiTextColor {
+    pub const ALL: [UiTextColor; 4] = [
+        UiTextColor::White,
+        UiTextColor::Cream,
+        UiTextColor::Yellow,
+        UiTextColor::Cyan,
+    ];
+
+    pub fn color(self) -> Color {
+        match self {
+            UiTextColor::White => Color::WHITE,
+            UiTextColor::Cream => Color::rgb(1.0, 0.95, 0.8),
+            UiTextColor::Yellow => Color::rgb(1.0, 0.9, 0.3),
+            UiTextColor::Cyan => Color::rgb(0.5, 0.95, 1.0),
+        }
+    }
+}
+
+/// Corners of the screen the scoreboard can sit in
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScoreboardCorner {
+    TopLeft,
+    TopRight,
+    #[default]
+    BottomLeft,
+    BottomRight,
+}
+
+impl ScoreboardCorner {
+    /// Gap between the scoreboard and the edges of the screen
+    const MARGIN: Val = Val::Percent(2.0);
+
+    /// Where the scoreboard goes, for a `PositionType::Absolute` style
+    pub fn position(self) -> UiRect {
+        let (top, bottom) = match self {
+            ScoreboardCorner::TopLeft | ScoreboardCorner::TopRight => {
+                (Self::MARGIN, Val::Undefined)
+            }
+            ScoreboardCorner::BottomLeft | ScoreboardCorner::BottomRight => {
+                (Val::Undefined, Self::MARGIN)
+            }
+        };
+        let (left, right) = match self {
+            ScoreboardCorner::TopLeft | ScoreboardCorner::BottomLeft => {
+                (Self::MARGIN, Val::Undefined)
+            }
+            ScoreboardCorner::TopRight | ScoreboardCorner::BottomRight => {
+                (Val::Undefined, Self::MARGIN)
+            }
+        };
+        UiRect {
+            left,
+            right,
+            top,
+            bottom,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            ScoreboardCorner::TopLeft => "Top Left",
+            ScoreboardCorner::TopRight => "Top Right",
+            ScoreboardCorner::BottomLeft => "Bottom Left",
+            ScoreboardCorner::BottomRight => "Bottom Right",
+        }
+    }
+}
+
+/// What happens to the pattern after a game ending mistake
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FailurePolicy {
+    /// Game over, and the next game starts from scratch
+    #[default]
+    ClearPattern,
+    /// Try the same pattern again
+    KeepPattern,
+    /// Drop the last button and try again
+    ShrinkByOne,
+}
+
+/// How hard the lamp pulses on each beat of the demo
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BeatPulse {
+    #[default]
+    Off,
+    Subtle,
+    Dramatic,
+}
+
+impl BeatPulse {
+    /// How long each beat dims the lamp for, and how much of its light it
+    /// takes away
+    pub fn envelope(self) -> Option<(f32, f32)> {
+        match self {
+            BeatPulse::Off => None,
+            BeatPulse::Subtle => Some((0.15, 0.25)),
+            BeatPulse::Dramatic => Some((0.3, 0.75)),
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            BeatPulse::Off => "Off",
+            BeatPulse::Subtle => "Subtle",
+            BeatPulse::Dramatic => "Dramatic",
+        }
+    }
+}
+
+/// How much of the pattern is shown each round
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DemoMode {
+    /// The whole pattern, like classic Simon
+    #[default]
+    FullReplay,
+    /// Only the newly added button
+    NewOnly,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            reduced_motion: false,
+            haptics: true,
+            glow_strength: 1.0,
+            bloom: true,
+            flat_view: false,
+            skip_demo: false,
+            high_contrast: false,
+            demo_mode: DemoMode::FullReplay,
+            desk_theme: DeskTheme::Plain,
+            buffer_input: false,
+            fov: 45.0,
+            varied_buttons: false,
+            game_speed: GameSpeed::default(),
+            sound_pack: SoundPack::Classic,
+            accessibility_timing: 1.0,
+            failure_policy: FailurePolicy::ClearPattern,
+            round_wipe: true,
+            max_length: None,
+            panned_audio: true,
+            starting_level: 1,
+            beat_pulse: BeatPulse::Off,
+            auto_resume: false,
+            press_on_release: false,
+            pattern_preview: None,
+            preview_ranked: false,
+            fast_forward: None,
+            ui_text_color: UiTextColor::White,
+            idle_hint: true,
+            performance_mode: false,
+            msaa_samples: 4,
+            mirror_layout: false,
+            backdrop: false,
+            peeks: 1,
+            hover_preview: false,
+            turn_timer: None,
+            no_repeats: false,
+            desk_tilt: 30.0,
+            reverse_at_level: None,
+            second_chance: false,
+            scoreboard_corner: ScoreboardCorner::BottomLeft,
+            warm_up: false,
+            music_ducking: Some(0.5),
+            duck_release: 0.5,
+            button_letters: false,
+            adaptive_difficulty: false,
+            forgiveness: 0,
+            camera_orbit: None,
+            hardware_strict: true,
+            remaining_presses: false,
+        }
+    }
+}
+
+impl Settings {
+    /// Reads the settings file, falling back to the defaults
+    fn load() -> Self {
+        read_ron(SETTINGS_PATH)
+    }
+
+    /// How brightly lit buttons glow, including the high contrast boost
+    pub fn glow(&self) -> f32 {
+        if self.high_contrast {
+            self.glow_strength * HIGH_CONTRAST_GLOW
+        } else {
+            self.glow_strength
+        }
+    }
+
+    /// A demo duration in seconds, at the `GameSpeed` and stretched by the
+    /// accessibility timing
+    pub fn demo_duration(&self, seconds: f32) -> f32 {
+        let stretch = self
+            .accessibility_timing
+            .clamp(1.0, MAX_ACCESSIBILITY_TIMING);
+        self.game_speed.scale(seconds) * stretch
+    }
+
+    /// Whether the pattern preview keeps runs off the high score
+    pub fn preview_unranked(&self) -> bool {
+        self.pattern_preview.is_some() && !self.preview_ranked
+    }
+
+    /// How much faster a demo button is shown, only the newest is never sped up
+    pub fn demo_speedup(&self, newest: bool) -> f32 {
+        match self.fast_forward {
+            Some(speedup) if !newest => speedup,
+            _ => 1.0,
+        }
+    }
+
+    /// Whether a pattern this long has to be repeated backwards
+    pub fn reverses_input(&self, length: usize) -> bool {
+        self.reverse_at_level.is_some_and(|level| length > level)
+    }
+
+    /// The MSAA samples to use, rounded to a count wgpu supports
+    pub fn msaa(&self) -> u32 {
+        if self.msaa_samples > 1 {
+            4
+        } else {
+            1
+        }
+    }
+
+    /// Writes the settings file
+    fn save(&self) {
+        write_ron(SETTINGS_PATH, self);
+    }
+}
+
+/// The color of each button, loaded from the palette file
+#[derive(Resource, Debug, Clone)]
+pub struct ButtonPalette(HashMap<Button, Color>);
+
+impl Default for ButtonPalette {
+    fn default() -> Self {
+        Self(
+            Button::ALL
+                .into_iter()
+                .map(|button| (button, Self::default_color(button)))
+                .collect(),
+        )
+    }
+}
+
+impl ButtonPalette {
+    /// The color of `button`
+    pub fn color(&self, button: Button) -> Color {
+        self.0
+            .get(&button)
+            .copied()
+            .unwrap_or_else(|| Self::default_color(button))
+    }
+
+    /// The classic colors
+    fn default_color(button: Button) -> Color {
+        match button {
+            Button::Red => Color::RED,
+            Button::Green => Color::GREEN,
+            Button::Blue => Color::BLUE,
+            Button::Yellow => Color::YELLOW,
+        }
+    }
+
+    /// Reads the palette file, falling back to the default colors for missing buttons
+    fn load() -> Self {
+        let contents = match fs::read_to_string(PALETTE_PATH) {
+            Ok(contents) => contents,
+            Err(error) => {
+                warn!("Couldn't read {PALETTE_PATH}, using default colors: {error}");
+                return Self::default();
+            }
+        };
+        let mut colors: HashMap<Button, Color> = match ron::from_str(&contents) {
+            Ok(colors) => colors,
+            Err(error) => {
+                warn!("Couldn't parse {PALETTE_PATH}, using default colors: {error}");
+                return Self::default();
+            }
+        };
+        for button in Button::ALL {
+            colors.entry(button).or_insert_with(|| {
+                warn!("{PALETTE_PATH} has no color for {button:?}, using the default");
+                Self::default_color(button)
+            });
+        }
+        Self(colors)
+    }
+}
+
+/// A setting that can be changed from the settings screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Setting {
+    ReducedMotion,
+    Haptics,
+    GlowStrength,
+    Bloom,
+    FlatView,
+    SkipDemo,
+    HighContrast,
+    DemoMode,
+    DeskTheme,
+    BufferInput,
+    Fov,
+    VariedButtons,
+    GameSpeed,
+    SoundPack,
+    AccessibilityTiming,
+    FailurePolicy,
+    RoundWipe,
+    MaxLength,
+    PannedAudio,
+    StartingLevel,
+    BeatPulse,
+    AutoResume,
+    PressOnRelease,
+    PatternPreview,
+    PreviewRanked,
+    FastForward,
+    UiTextColor,
+    IdleHint,
+    PerformanceMode,
+    Msaa,
+    MirrorLayout,
+    Backdrop,
+    Peeks,
+    HoverPreview,
+    TurnTimer,
+    NoRepeats,
+    DeskTilt,
+    ReverseAtLevel,
+    SecondChance,
+    ScoreboardCorner,
+    WarmUp,
+    MusicDucking,
+    DuckRelease,
+    ButtonLetters,
+    AdaptiveDifficulty,
+    Forgiveness,
+    CameraOrbit,
+    HardwareStrict,
+    RemainingPresses,
+}
+
+/// The pages the settings screen is split into, so each one fits on screen
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SettingsPage {
+    #[default]
+    Accessibility,
+    Rules,
+    Timing,
+    Assists,
+    Graphics,
+    Scene,
+    Audio,
+}
+
+impl SettingsPage {
+    const ALL: [SettingsPage; 7] = [
+        SettingsPage::Accessibility,
+        SettingsPage::Rules,
+        SettingsPage::Timing,
+        SettingsPage::Assists,
+        SettingsPage::Graphics,
+        SettingsPage::Scene,
+        SettingsPage::Audio,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SettingsPage::Accessibility => "Accessibility",
+            SettingsPage::Rules => "Rules",
+            SettingsPage::Timing => "Timing",
+            SettingsPage::Assists => "Assists",
+            SettingsPage::Graphics => "Graphics",
+            SettingsPage::Scene => "Scene",
+            SettingsPage::Audio => "Audio",
+        }
+    }
+
+    /// The page `offset` pages after this one, wrapping around
+    pub fn step(self, offset: isize) -> Self {
+        let index = Self::ALL.iter().position(|page| *page == self).unwrap_or(0);
+        let count = Self::ALL.len() as isize;
+        Self::ALL[(index as isize + offset).rem_euclid(count) as usize]
+    }
+}
+
+impl Setting {
+    /// Every setting, in the order they are listed
+    pub const ALL: [Setting; 49] = [
+        // First, so it's easy to find
+        Setting::ReducedMotion,
+        Setting::Haptics,
+        Setting::GlowStrength,
+        Setting::Bloom,
+        Setting::FlatView,
+        Setting::SkipDemo,
+        Setting::HighContrast,
+        Setting::DemoMode,
+        Setting::DeskTheme,
+        Setting::BufferInput,
+        Setting::Fov,
+        Setting::VariedButtons,
+        Setting::GameSpeed,
+        Setting::SoundPack,
+        Setting::AccessibilityTiming,
+        Setting::FailurePolicy,
+        Setting::RoundWipe,
+        Setting::MaxLength,
+        Setting::PannedAudio,
+        Setting::StartingLevel,
+        Setting::BeatPulse,
+        Setting::AutoResume,
+        Setting::PressOnRelease,
+        Setting::PatternPreview,
+        Setting::PreviewRanked,
+        Setting::FastForward,
+        Setting::UiTextColor,
+        Setting::IdleHint,
+        Setting::PerformanceMode,
+        Setting::Msaa,
+        Setting::MirrorLayout,
+        Setting::Backdrop,
+        Setting::Peeks,
+        Setting::HoverPreview,
+        Setting::TurnTimer,
+        Setting::NoRepeats,
+        Setting::DeskTilt,
+        Setting::ReverseAtLevel,
+        Setting::SecondChance,
+        Setting::ScoreboardCorner,
+        Setting::WarmUp,
+        Setting::MusicDucking,
+        Setting::DuckRelease,
+        Setting::ButtonLetters,
+        Setting::AdaptiveDifficulty,
+        Setting::Forgiveness,
+        Setting::CameraOrbit,
+        Setting::HardwareStrict,
+        Setting::RemainingPresses,
+    ];
+
+    /// The page of the settings screen it's listed on
+    pub fn page(self) -> SettingsPage {
+        match self {
+            Setting::ReducedMotion
+            | Setting::Haptics
+            | Setting::HighContrast
+            | Setting::AccessibilityTiming
+            | Setting::UiTextColor
+            | Setting::ButtonLetters
+            | Setting::MirrorLayout
+            | Setting::PressOnRelease => SettingsPage::Accessibility,
+            Setting::DemoMode
+            | Setting::FailurePolicy
+            | Setting::MaxLength
+            | Setting::StartingLevel
+            | Setting::NoRepeats
+            | Setting::ReverseAtLevel
+            | Setting::SecondChance
+            | Setting::WarmUp
+            | Setting::Forgiveness
+            | Setting::HardwareStrict => SettingsPage::Rules,
+            Setting::SkipDemo
+            | Setting::BufferInput
+            | Setting::GameSpeed
+            | Setting::AutoResume
+            | Setting::FastForward
+            | Setting::TurnTimer
+            | Setting::AdaptiveDifficulty => SettingsPage::Timing,
+            Setting::PatternPreview
+            | Setting::PreviewRanked
+            | Setting::IdleHint
+            | Setting::Peeks
+            | Setting::HoverPreview
+            | Setting::RemainingPresses => SettingsPage::Assists,
+            Setting::GlowStrength
+            | Setting::Bloom
+            | Setting::FlatView
+            | Setting::Fov
+            | Setting::PerformanceMode
+            | Setting::Msaa => SettingsPage::Graphics,
+            Setting::DeskTheme
+            | Setting::VariedButtons
+            | Setting::RoundWipe
+            | Setting::BeatPulse
+            | Setting::Backdrop
+            | Setting::DeskTilt
+            | Setting::ScoreboardCorner
+            | Setting::CameraOrbit => SettingsPage::Scene,
+            Setting::SoundPack
+            | Setting::PannedAudio
+            | Setting::MusicDucking
+            | Setting::DuckRelease => SettingsPage::Audio,
+        }
+    }
+
+    /// The text shown on the setting's menu item
+    pub fn label(self, settings: &Settings) -> String {
+        match self {
+            Setting::ReducedMotion => {
+                format!("Reduced Motion: {}", on_off(settings.reduced_motion))
+            }
+            Setting::Haptics => format!("Haptics: {}", on_off(settings.haptics)),
+            Setting::GlowStrength => format!("Glow: {}x", settings.glow_strength),
+            Setting::Bloom => format!("Bloom: {}", on_off(settings.bloom)),
+            Setting::FlatView => {
+                format!("View: {}", if settings.flat_view { "Flat" } else { "3D" })
+            }
+            Setting::SkipDemo => format!("Skip Demo (Space): {}", on_off(settings.skip_demo)),
+            Setting::HighContrast => format!("High Contrast: {}", on_off(settings.high_contrast)),
+            Setting::DemoMode => format!(
+                "Demo: {}",
+                match settings.demo_mode {
+                    DemoMode::FullReplay => "Full Pattern",
+                    DemoMode::NewOnly => "New Button Only",
+                }
+            ),
+            Setting::DeskTheme => format!("Desk: {:?}", settings.desk_theme),
+            Setting::BufferInput => format!("Early Presses: {}", on_off(settings.buffer_input)),
+            Setting::Fov => format!("Field of View: {}°", settings.fov),
+            Setting::VariedButtons => format!("Mixed Shapes: {}", on_off(settings.varied_buttons)),
+            Setting::GameSpeed => format!("Speed: {}x", settings.game_speed.0),
+            Setting::SoundPack => format!("Sounds: {:?}", settings.sound_pack),
+            Setting::AccessibilityTiming => {
+                format!("Accessibility Timing: {}x", settings.accessibility_timing)
+            }
+            Setting::FailurePolicy => format!(
+                "On Mistake: {}",
+                match settings.failure_policy {
+                    FailurePolicy::ClearPattern => "Game Over",
+                    FailurePolicy::KeepPattern => "Retry",
+                    FailurePolicy::ShrinkByOne => "Retry One Shorter",
+                }
+            ),
+            Setting::RoundWipe => format!("Round Transitions: {}", on_off(settings.round_wipe)),
+            Setting::MaxLength => match settings.max_length {
+                Some(length) => format!("Win At: {length} Buttons"),
+                None => "Win At: Never".into(),
+            },
+            Setting::PannedAudio => format!("Stereo Sounds: {}", on_off(settings.panned_audio)),
+            Setting::StartingLevel => format!("Starting Level: {}", settings.starting_level),
+            Setting::BeatPulse => format!("Beat Pulse: {}", settings.beat_pulse.label()),
+            Setting::AutoResume => format!("Resume On Focus: {}", on_off(settings.auto_resume)),
+            Setting::PatternPreview => match settings.pattern_preview {
+                Some(seconds) => format!("Pattern Preview Assist: {seconds}s"),
+                None => "Pattern Preview Assist: Off".into(),
+            },
+            Setting::PreviewRanked => format!(
+                "Preview Assist High Scores: {}",
+                on_off(settings.preview_ranked)
+            ),
+            Setting::FastForward => match settings.fast_forward {
+                Some(speedup) => format!("Fast Forward Seen Buttons: {speedup}x"),
+                None => "Fast Forward Seen Buttons: Off".into(),
+            },
+            Setting::UiTextColor => format!("Text Color: {:?}", settings.ui_text_color),
+            Setting::IdleHint => format!("Idle Hints: {}", on_off(settings.idle_hint)),
+            Setting::PerformanceMode => {
+                format!("Performance Mode: {}", on_off(settings.performance_mode))
+            }
+            Setting::Msaa => match settings.msaa_samples {
+                1 => "Anti-Aliasing: Off".into(),
+                samples => format!("Anti-Aliasing: {samples}x"),
+            },
+            Setting::MirrorLayout => format!("Mirror Layout: {}", on_off(settings.mirror_layout)),
+            Setting::Backdrop => format!("Backdrop Grid: {}", on_off(settings.backdrop)),
+            Setting::Peeks => match settings.peeks {
+                0 => "Peeks Per Run: Off".into(),
+                peeks => format!("Peeks Per Run: {peeks}"),
+            },
+            Setting::HoverPreview => {
+                format!("Hover Sound Preview: {}", on_off(settings.hover_preview))
+            }
+            Setting::TurnTimer => match settings.turn_timer {
+                None => "Turn Timer: Off".into(),
+                Some(seconds) => format!("Turn Timer: {seconds}s"),
+            },
+            Setting::NoRepeats => format!("No Repeats: {}", on_off(settings.no_repeats)),
+            Setting::DeskTilt => format!("Desk Tilt: {}°", settings.desk_tilt),
+            Setting::ReverseAtLevel => match settings.reverse_at_level {
+                None => "Reverse After: Off".into(),
+                Some(level) => format!("Reverse After: Length {level}"),
+            },
+            Setting::SecondChance => format!("Second Chance: {}", on_off(settings.second_chance)),
+            Setting::ScoreboardCorner => {
+                format!("Scoreboard: {}", settings.scoreboard_corner.label())
+            }
+            Setting::WarmUp => format!("Warm-Up Round: {}", on_off(settings.warm_up)),
+            Setting::MusicDucking => match settings.music_ducking {
+                None => "Music Ducking: Off".into(),
+                Some(amount) => format!("Music Ducking: {}%", (amount * 100.0).round()),
+            },
+            Setting::DuckRelease => format!("Duck Release: {}s", settings.duck_release),
+            Setting::ButtonLetters => {
+                format!("Button Letters: {}", on_off(settings.button_letters))
+            }
+            Setting::AdaptiveDifficulty => format!(
+                "Adaptive Difficulty: {}",
+                on_off(settings.adaptive_difficulty)
+            ),
+            Setting::Forgiveness => match settings.forgiveness {
+                0 => "Forgiveness: Off".into(),
+                forgiveness => format!("Forgiveness: {forgiveness} Per Round"),
+            },
+            Setting::CameraOrbit => match settings.camera_orbit {
+                None => "Camera Orbit: Off".into(),
+                Some(speed) => format!("Camera Orbit: {speed}°/s"),
+            },
+            Setting::HardwareStrict => format!(
+                "Classic Hardware Strict: {}",
+                on_off(settings.hardware_strict)
+            ),
+            Setting::RemainingPresses => {
+                format!("Presses Left: {}", on_off(settings.remaining_presses))
+            }
+            Setting::PressOnRelease => format!(
+                "Press On: {}",
+                if settings.press_on_release {
+                    "Release"
+                } else {
+                    "Click"
+                }
+            ),
+        }
+    }
+
+    /// Changes the setting to its next value
+    pub fn cycle(self, settings: &mut Settings) {
+        match self {
+            Setting::ReducedMotion => settings.reduced_motion = !settings.reduced_motion,
+            Setting::Haptics => settings.haptics = !settings.haptics,
+            Setting::GlowStrength => {
+                settings.glow_strength = next_in(&GLOW_STRENGTHS, settings.glow_strength)
+            }
+            Setting::Bloom => settings.bloom = !settings.bloom,
+            Setting::FlatView => settings.flat_view = !settings.flat_view,
+            Setting::SkipDemo => settings.skip_demo = !settings.skip_demo,
+            Setting::HighContrast => settings.high_contrast = !settings.high_contrast,
+            Setting::DemoMode => {
+                settings.demo_mode = match settings.demo_mode {
+                    DemoMode::FullReplay => DemoMode::NewOnly,
+                    DemoMode::NewOnly => DemoMode::FullReplay,
+                }
+            }
+            Setting::DeskTheme => settings.desk_theme = next_in(&DESK_THEMES, settings.desk_theme),
+            Setting::BufferInput => settings.buffer_input = !settings.buffer_input,
+            Setting::Fov => settings.fov = next_in(&FOVS, settings.fov),
+            Setting::VariedButtons => settings.varied_buttons = !settings.varied_buttons,
+            Setting::GameSpeed => {
+                settings.game_speed = GameSpeed(next_in(&GAME_SPEEDS, settings.game_speed.0))
+            }
+            Setting::SoundPack => settings.sound_pack = next_in(&SOUND_PACKS, settings.sound_pack),
+            Setting::AccessibilityTiming => {
+                settings.accessibility_timing =
+                    next_in(&ACCESSIBILITY_TIMINGS, settings.accessibility_timing)
+            }
+            Setting::FailurePolicy => {
+                settings.failure_policy = next_in(&FAILURE_POLICIES, settings.failure_policy)
+            }
+            Setting::RoundWipe => settings.round_wipe = !settings.round_wipe,
+            Setting::MaxLength => settings.max_length = next_in(&MAX_LENGTHS, settings.max_length),
+            Setting::PannedAudio => settings.panned_audio = !settings.panned_audio,
+            Setting::StartingLevel => {
+                settings.starting_level = next_in(&STARTING_LEVELS, settings.starting_level)
+            }
+            Setting::BeatPulse => settings.beat_pulse = next_in(&BEAT_PULSES, settings.beat_pulse),
+            Setting::AutoResume => settings.auto_resume = !settings.auto_resume,
+            Setting::PressOnRelease => settings.press_on_release = !settings.press_on_release,
+            Setting::PatternPreview => {
+                settings.pattern_preview = next_in(&PATTERN_PREVIEWS, settings.pattern_preview)
+            }
+            Setting::PreviewRanked => settings.preview_ranked = !settings.preview_ranked,
+            Setting::FastForward => {
+                settings.fast_forward = next_in(&FAST_FORWARDS, settings.fast_forward)
+            }
+            Setting::IdleHint => settings.idle_hint = !settings.idle_hint,
+            Setting::PerformanceMode => settings.performance_mode = !settings.performance_mode,
+            Setting::Msaa => settings.msaa_samples = next_in(&MSAA_SAMPLES, settings.msaa_samples),
+            Setting::MirrorLayout => settings.mirror_layout = !settings.mirror_layout,
+            Setting::Backdrop => settings.backdrop = !settings.backdrop,
+            Setting::Peeks => settings.peeks = next_in(&PEEK_COUNTS, settings.peeks),
+            Setting::HoverPreview => settings.hover_preview = !settings.hover_preview,
+            Setting::TurnTimer => settings.turn_timer = next_in(&TURN_TIMERS, settings.turn_timer),
+            Setting::NoRepeats => settings.no_repeats = !settings.no_repeats,
+            Setting::DeskTilt => settings.desk_tilt = next_in(&DESK_TILTS, settings.desk_tilt),
+            Setting::ReverseAtLevel => {
+                settings.reverse_at_level = next_in(&REVERSE_AT_LEVELS, settings.reverse_at_level);
+            }
+            Setting::SecondChance => settings.second_chance = !settings.second_chance,
+            Setting::ScoreboardCorner => {
+                settings.scoreboard_corner =
+                    next_in(&SCOREBOARD_CORNERS, settings.scoreboard_corner);
+            }
+            Setting::WarmUp => settings.warm_up = !settings.warm_up,
+            Setting::MusicDucking => {
+                settings.music_ducking = next_in(&MUSIC_DUCKINGS, settings.music_ducking);
+            }
+            Setting::DuckRelease => {
+                settings.duck_release = next_in(&DUCK_RELEASES, settings.duck_release);
+            }
+            Setting::ButtonLetters => settings.button_letters = !settings.button_letters,
+            Setting::AdaptiveDifficulty => {
+                settings.adaptive_difficulty = !settings.adaptive_difficulty;
+            }
+            Setting::Forgiveness => {
+                settings.forgiveness = next_in(&FORGIVENESS_COUNTS, settings.forgiveness);
+            }
+            Setting::CameraOrbit => {
+                settings.camera_orbit = next_in(&CAMERA_ORBITS, settings.camera_orbit);
+            }
+            Setting::HardwareStrict => settings.hardware_strict = !settings.hardware_strict,
+            Setting::RemainingPresses => settings.remaining_presses = !settings.remaining_presses,
+            Setting::UiTextColor => {
+                settings.ui_text_color = next_in(&UiTextColor::ALL, settings.ui_text_color)
+            }
+        }
+    }
+}
+
+const GLOW_STRENGTHS: [f32; 4] = [0.5, 1.0, 2.0, 4.0];
+const GAME_SPEEDS: [f32; 5] = [0.5, 0.75, 1.0, 1.5, 2.0];
+const FOVS: [f32; 5] = [30.0, 45.0, 60.0, 75.0, 90.0];
+const DESK_TILTS: [f32; 4] = [15.0, 30.0, 45.0, 60.0];
+const DESK_THEMES: [DeskTheme; 3] = [DeskTheme::Plain, DeskTheme::Wood, DeskTheme::Metal];
+const ACCESSIBILITY_TIMINGS: [f32; 4] = [1.0, 1.5, 2.0, 3.0];
+const MAX_ACCESSIBILITY_TIMING: f32 = 3.0;
+const FAILURE_POLICIES: [FailurePolicy; 3] = [
+    FailurePolicy::ClearPattern,
+    FailurePolicy::KeepPattern,
+    FailurePolicy::ShrinkByOne,
+];
+const MAX_LENGTHS: [Option<usize>; 4] = [None, Some(8), Some(16), Some(32)];
+const STARTING_LEVELS: [usize; 4] = [1, 3, 5, 8];
+const PATTERN_PREVIEWS: [Option<f32>; 4] = [None, Some(1.0), Some(2.0), Some(3.0)];
+const FAST_FORWARDS: [Option<f32>; 4] = [None, Some(2.0), Some(2.5), Some(3.0)];
+const PEEK_COUNTS: [usize; 4] = [0, 1, 2, 3];
+const FORGIVENESS_COUNTS: [usize; 4] = [0, 1, 2, 3];
+const CAMERA_ORBITS: [Option<f32>; 4] = [None, Some(5.0), Some(10.0), Some(20.0)];
+const TURN_TIMERS: [Option<f32>; 4] = [None, Some(3.0), Some(5.0), Some(8.0)];
+const MUSIC_DUCKINGS: [Option<f32>; 4] = [None, Some(0.25), Some(0.5), Some(0.75)];
+const DUCK_RELEASES: [f32; 3] = [0.25, 0.5, 1.0];
+const REVERSE_AT_LEVELS: [Option<usize>; 4] = [None, Some(5), Some(10), Some(15)];
+/// wgpu only supports 1 or 4 samples for now, so there's no 2x
+const MSAA_SAMPLES: [u32; 2] = [1, 4];
+const SCOREBOARD_CORNERS: [ScoreboardCorner; 4] = [
+    ScoreboardCorner::TopLeft,
+    ScoreboardCorner::TopRight,
+    ScoreboardCorner::BottomLeft,
+    ScoreboardCorner::BottomRight,
+];
+const BEAT_PULSES: [BeatPulse; 3] = [BeatPulse::Off, BeatPulse::Subtle, BeatPulse::Dramatic];
+/// Only the packs whose sounds are in the assets folder
+const SOUND_PACKS: [SoundPack; 1] = [SoundPack::Classic];
+/// Extra glow multiplier in high contrast
+const HIGH_CONTRAST_GLOW: f32 = 2.0;
+
+/// The value after `current` in `values`, wrapping around
+fn next_in<T: Copy + PartialEq>(values: &[T], current: T) -> T {
+    let index = values.iter().position(|value| *value == current);
+    values[index.map_or(0, |index| (index + 1) % values.len())]
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "On"
+    } else {
+        "Off"
+    }
+}
+
+/// Saves the settings whenever they change
+fn save_settings(settings: Res<Settings>) {
+    if settings.is_changed() && !settings.is_added() {
+        settings.save();
+    }
+}
+
+/// Turns on performance mode on first launch if the game is rendering on the CPU
+fn detect_low_end_hardware(
+    mut settings: ResMut<Settings>,
+    adapter_info: Option<Res<RenderAdapterInfo>>,
+) {
+    let Some(adapter_info) = adapter_info else {
+        return;
+    };
+    // Bevy doesn't re-export `wgpu::DeviceType`, and depending on wgpu directly
+    // would mean keeping its version in step with bevy's by hand
+    if format!("{:?}", adapter_info.device_type) == "Cpu" {
+        info!("Rendering on the CPU, turning on performance mode");
+        settings.performance_mode = true;
+    }
+}
+
+/// Saves the settings one last time when the app quits
+fn save_settings_on_exit(mut exit_reader: EventReader<AppExit>, settings: Res<Settings>) {
+    if exit_reader.iter().last().is_some() {
+        settings.save();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_settings_page_fits_on_screen() {
+        // Keeps each page to two columns in the default 1280x720 window
+        for page in SettingsPage::ALL {
+            let count = Setting::ALL
+                .into_iter()
+                .filter(|setting| setting.page() == page)
+                .count();
+            assert!(count <= 10, "{page:?} has {count} settings");
+        }
+    }
+}
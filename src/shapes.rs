@@ -0,0 +1,84 @@
+use bevy::prelude::{shape, *};
+
+use crate::{settings::Settings, Button};
+
+/// Button meshes, and mixed shapes and sizes for a harder visual challenge
+pub struct ShapesPlugin;
+
+impl Plugin for ShapesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system_to_stage(StartupStage::PreStartup, setup_button_meshes)
+            .add_system(apply_button_shapes);
+    }
+}
+
+/// How big classic buttons are
+pub const CLASSIC_BUTTON_SCALE: Vec3 = Vec3::splat(0.2);
+
+/// Meshes for the classic cubes and the mixed shapes
+#[derive(Resource)]
+pub struct ButtonMeshes {
+    pub classic: Handle<Mesh>,
+    sphere: Handle<Mesh>,
+    capsule: Handle<Mesh>,
+}
+
+impl ButtonMeshes {
+    /// The mesh and scale for `button` when shapes are mixed
+    fn varied(&self, button: Button) -> (Handle<Mesh>, Vec3) {
+        match button {
+            Button::Red => (self.classic.clone(), Vec3::splat(0.14)),
+            Button::Green => (self.sphere.clone(), Vec3::splat(0.2)),
+            Button::Blue => (self.capsule.clone(), Vec3::splat(0.18)),
+            Button::Yellow => (self.classic.clone(), Vec3::new(0.22, 0.2, 0.1)),
+        }
+    }
+}
+
+/// Creates the button meshes
+fn setup_button_meshes(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    commands.insert_resource(ButtonMeshes {
+        classic: meshes.add(shape::Box::new(1.0, 1.0, 1.0).into()),
+        sphere: meshes.add(
+            shape::UVSphere {
+                radius: 0.5,
+                ..Default::default()
+            }
+            .into(),
+        ),
+        capsule: meshes.add(
+            shape::Capsule {
+                radius: 0.3,
+                depth: 0.4,
+                ..Default::default()
+            }
+            .into(),
+        ),
+    });
+}
+
+/// Swaps the button meshes when the setting changes
+///
+/// Picking casts against the meshes themselves, so the click targets follow the shapes
+fn apply_button_shapes(
+    settings: Res<Settings>,
+    button_meshes: Res<ButtonMeshes>,
+    mut buttons: Query<(&Button, &mut Handle<Mesh>, &mut Transform)>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    for (button, mut mesh, mut transform) in buttons.iter_mut() {
+        let (new_mesh, scale) = if settings.varied_buttons {
+            button_meshes.varied(*button)
+        } else {
+            (button_meshes.classic.clone(), CLASSIC_BUTTON_SCALE)
+        };
+        if *mesh != new_mesh {
+            *mesh = new_mesh;
+        }
+        if transform.scale != scale {
+            transform.scale = scale;
+        }
+    }
+}
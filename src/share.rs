@@ -0,0 +1,90 @@
+use arboard::Clipboard;
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{generator::Daily, menu::MenuAction, toast::Toast, AppState, Pattern, Score};
+
+/// Copies a spoiler-free summary of a daily run for sharing
+pub struct SharePlugin;
+
+impl Plugin for SharePlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(daily) = Daily::from_args() {
+            app.insert_resource(daily);
+        }
+        app.add_system(
+            copy_result
+                .run_in_state(AppState::GameOver)
+                .run_if_resource_exists::<Daily>(),
+        );
+    }
+}
+
+/// Squares in each line of the result
+const SQUARES_PER_LINE: usize = 10;
+const CLEARED_SQUARE: char = '🟩';
+const FAILED_SQUARE: char = '🟥';
+
+/// The `YYYY-MM-DD` date `days` after 1970-01-01
+fn format_date(days: u64) -> String {
+    // Howard Hinnant's `civil_from_days`, with years starting in March so
+    // leap days come last
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let march_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * march_month + 2) / 5 + 1;
+    let month = if march_month < 10 {
+        march_month + 3
+    } else {
+        march_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year}-{month:02}-{day:02}")
+}
+
+/// The date, the score and a square for each round, without giving away the
+/// pattern itself
+fn daily_result(day: u64, score: usize, length: usize) -> String {
+    // The last round is the one that was failed
+    let cleared = length.saturating_sub(1);
+    let squares: Vec<char> = std::iter::repeat_n(CLEARED_SQUARE, cleared)
+        .chain(std::iter::once(FAILED_SQUARE))
+        .collect();
+    let lines: Vec<String> = squares
+        .chunks(SQUARES_PER_LINE)
+        .map(|line| line.iter().collect())
+        .collect();
+    format!(
+        "Simon Daily {}\nScore: {score}\n{}",
+        format_date(day),
+        lines.join("\n")
+    )
+}
+
+/// Copies the result to the clipboard when "Copy Result" is clicked
+fn copy_result(
+    mut menu_events: EventReader<MenuAction>,
+    mut toast_writer: EventWriter<Toast>,
+    daily: Res<Daily>,
+    score: Res<Score>,
+    pattern: Res<Pattern>,
+) {
+    if !menu_events
+        .iter()
+        .any(|event| *event == MenuAction::CopyResult)
+    {
+        return;
+    }
+    let result = daily_result(daily.0, score.current, pattern.0.len());
+    match Clipboard::new().and_then(|mut clipboard| clipboard.set_text(result)) {
+        Ok(()) => toast_writer.send(Toast::new("Result copied!")),
+        Err(error) => {
+            warn!("Couldn't copy the result: {error}");
+            toast_writer.send(Toast::new("Couldn't copy the result"));
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use bevy::prelude::*;
+
+use crate::{effects::Lifetime, fonts::UiFont, settings::Settings};
+
+/// Short notifications that slide in from the corner and fade away
+pub struct ToastPlugin;
+
+impl Plugin for ToastPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Toast>()
+            .add_startup_system(setup_toasts)
+            .add_system(spawn_toasts)
+            .add_system(animate_toasts);
+    }
+}
+
+/// How long a toast stays up unless it asks for longer
+const TOAST_SECONDS: f32 = 3.0;
+/// How long a toast takes to slide in
+const TOAST_SLIDE_SECONDS: f32 = 0.25;
+/// How long a toast takes to fade out at the end
+const TOAST_FADE_SECONDS: f32 = 0.5;
+/// How far off the edge a toast starts, in pixels
+const TOAST_SLIDE_DISTANCE: f32 = 300.0;
+const TOAST_BACKGROUND: Color = Color::rgba(0.1, 0.1, 0.1, 0.8);
+
+/// Event for showing a notification
+pub struct Toast {
+    pub text: String,
+    pub seconds: f32,
+}
+
+impl Toast {
+    /// A toast that stays up for the usual time
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            seconds: TOAST_SECONDS,
+        }
+    }
+}
+
+/// Marker component for the corner the toasts stack up in
+#[derive(Component)]
+struct ToastStack;
+
+/// Marker component for a toast, despawned when its `Lifetime` runs out
+#[derive(Component)]
+struct ToastLabel;
+
+/// Spawns the corner for the toasts
+fn setup_toasts(mut commands: Commands) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Percent(2.0),
+                    right: Val::Percent(2.0),
+                    ..Default::default()
+                },
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::FlexEnd,
+                ..Default::default()
+            },
+            background_color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .insert(ToastStack);
+}
+
+/// Adds a label to the stack for each `Toast`, under the ones already showing
+fn spawn_toasts(
+    mut commands: Commands,
+    mut event_reader: EventReader<Toast>,
+    stacks: Query<Entity, With<ToastStack>>,
+    font: Res<UiFont>,
+    settings: Res<Settings>,
+) {
+    let Ok(stack) = stacks.get_single() else {
+        return;
+    };
+    for toast in event_reader.iter() {
+        let label = commands
+            .spawn(NodeBundle {
+                style: Style {
+                    margin: UiRect::all(Val::Px(4.0)),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    ..Default::default()
+                },
+                background_color: TOAST_BACKGROUND.into(),
+                ..Default::default()
+            })
+            .insert(ToastLabel)
+            .insert(Lifetime(Timer::from_seconds(
+                toast.seconds,
+                TimerMode::Once,
+            )))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    toast.text.clone(),
+                    TextStyle {
+                        font: font.handle.clone(),
+                        font_size: 28.0,
+                        color: settings.ui_text_color.color(),
+                    },
+                ));
+            })
+            .id();
+        commands.entity(stack).add_child(label);
+    }
+}
+
+/// Slides toasts in, unless motion is reduced, and fades them out at the end
+fn animate_toasts(
+    mut toasts: Query<(&Lifetime, &mut Style, &mut BackgroundColor, &Children), With<ToastLabel>>,
+    mut texts: Query<&mut Text>,
+    settings: Res<Settings>,
+) {
+    for (lifetime, mut style, mut background, children) in toasts.iter_mut() {
+        let slide = 1.0 - (lifetime.0.elapsed_secs() / TOAST_SLIDE_SECONDS).min(1.0);
+        style.position.right = if settings.reduced_motion {
+            Val::Undefined
+        } else {
+            Val::Px(-TOAST_SLIDE_DISTANCE * slide * slide)
+        };
+
+        let remaining = lifetime.0.duration().as_secs_f32() - lifetime.0.elapsed_secs();
+        let alpha = (remaining / TOAST_FADE_SECONDS).min(1.0);
+        background.0.set_a(TOAST_BACKGROUND.a() * alpha);
+        for child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                for section in text.sections.iter_mut() {
+                    section.style.color.set_a(alpha);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+use bevy_mod_picking::{PickingCamera, PickingSystem};
+use bevy_mod_raycast::RaycastMethod;
+
+/// Makes taps work like clicks, for both the buttons and the menus
+pub struct TouchPlugin;
+
+impl Plugin for TouchPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_to_stage(
+            CoreStage::First,
+            aim_picking_at_new_touch
+                .after(PickingSystem::UpdatePickSourcePositions)
+                .before(PickingSystem::BuildRays),
+        )
+        .add_system_to_stage(CoreStage::PostUpdate, release_finished_taps);
+    }
+}
+
+/// Points the picking ray at the newest tap
+///
+/// Picking otherwise aims at whichever touch it sees last, which can be a
+/// finger that's already resting on the screen.
+fn aim_picking_at_new_touch(
+    touches: Res<Touches>,
+    mut pick_sources: Query<(&mut PickingCamera, &Camera)>,
+) {
+    // Only one tap counts per frame, so simultaneous touches can't press twice
+    let Some(touch) = touches.iter_just_pressed().min_by_key(|touch| touch.id()) else {
+        return;
+    };
+    for (mut pick_source, camera) in pick_sources.iter_mut() {
+        if let Some(size) = camera.logical_target_size() {
+            let position = touch.position();
+            pick_source.cast_method =
+                RaycastMethod::Screenspace(Vec2::new(position.x, size.y - position.y));
+        }
+    }
+}
+
+/// Un-clicks anything that's still clicked once every finger and mouse button is up
+///
+/// A tap that starts and ends in the same frame leaves its target `Clicked`,
+/// which would swallow the next tap on it.
+fn release_finished_taps(
+    touches: Res<Touches>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    mut interactions: Query<&mut Interaction>,
+) {
+    if touches.iter().next().is_some() || mouse_buttons.pressed(MouseButton::Left) {
+        return;
+    }
+    for mut interaction in interactions.iter_mut() {
+        if *interaction == Interaction::Clicked {
+            *interaction = Interaction::None;
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use bevy::{prelude::*, ui::FocusPolicy};
+use iyes_loopless::prelude::*;
+
+use crate::{settings::Settings, AppState, FixedUpdate, SimonState};
+
+/// A quick fade between showing the pattern and repeating it
+pub struct TransitionPlugin;
+
+impl Plugin for TransitionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_enter_system(SimonState::MonkeySee, start_wipe)
+            .add_enter_system(SimonState::MonkeyDo, start_wipe)
+            .add_exit_system(SimonState::MonkeySee, end_wipe)
+            .add_exit_system(SimonState::MonkeyDo, end_wipe)
+            .add_system(play_wipe.run_not_in_state(AppState::Paused));
+    }
+}
+
+const WIPE_SECONDS: f32 = 0.3;
+/// How dark the screen gets halfway through the wipe
+const WIPE_DARKNESS: f32 = 0.8;
+
+/// A fade over the whole screen
+#[derive(Component)]
+struct Wipe(Timer);
+
+/// Covers the screen and holds the demo until the wipe is done
+fn start_wipe(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut timesteps: ResMut<FixedTimesteps>,
+) {
+    if !settings.round_wipe || settings.reduced_motion {
+        return;
+    }
+    if let Some(timestep) = timesteps.get_mut(FixedUpdate.as_str()) {
+        timestep.pause();
+    }
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                ..Default::default()
+            },
+            background_color: Color::NONE.into(),
+            // Clicks go through to the buttons, so nothing pressed during the wipe is lost
+            focus_policy: FocusPolicy::Pass,
+            ..Default::default()
+        })
+        .insert(Wipe(Timer::from_seconds(WIPE_SECONDS, TimerMode::Once)));
+}
+
+/// Fades to dark and back, then lets the demo carry on
+fn play_wipe(
+    mut commands: Commands,
+    mut wipes: Query<(Entity, &mut Wipe, &mut BackgroundColor)>,
+    mut timesteps: ResMut<FixedTimesteps>,
+    time: Res<Time>,
+) {
+    for (entity, mut wipe, mut color) in wipes.iter_mut() {
+        // Resuming from the pause screen restarts the timestep, so keep holding it
+        let Some(timestep) = timesteps.get_mut(FixedUpdate.as_str()) else {
+            continue;
+        };
+        if wipe.0.tick(time.delta()).finished() {
+            timestep.unpause();
+            commands.entity(entity).despawn_recursive();
+        } else {
+            timestep.pause();
+            let darkness = 1.0 - (2.0 * wipe.0.percent() - 1.0).abs();
+            color.0 = Color::rgba(0.0, 0.0, 0.0, darkness * WIPE_DARKNESS);
+        }
+    }
+}
+
+/// Removes an unfinished wipe when leaving the round
+fn end_wipe(
+    mut commands: Commands,
+    wipes: Query<Entity, With<Wipe>>,
+    mut timesteps: ResMut<FixedTimesteps>,
+) {
+    for entity in wipes.iter() {
+        commands.entity(entity).despawn_recursive();
+        if let Some(timestep) = timesteps.get_mut(FixedUpdate.as_str()) {
+            timestep.unpause();
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use bevy::{ecs::event::ManualEventReader, prelude::*};
+use iyes_loopless::prelude::*;
+
+use crate::{
+    adaptive::{Performance, CLOSE_CALL_TIME_LEFT},
+    preview::PatternPreview,
+    settings::Settings,
+    AdvanceDelay, AppState, ButtonTimings, GameMode, SimonEvent, SimonState,
+};
+
+/// Fails the round if the player takes too long between presses, with a bar
+/// showing the time left
+pub struct TurnTimerPlugin;
+
+impl Plugin for TurnTimerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup_turn_timer_bar)
+            .add_enter_system(SimonState::MonkeyDo, start_turn_timer)
+            .add_exit_system(SimonState::MonkeyDo, end_turn_timer)
+            // Stops once the round is decided
+            .add_system(
+                tick_turn_timer
+                    .run_in_state(AppState::Playing)
+                    .run_in_state(SimonState::MonkeyDo)
+                    .run_if_resource_exists::<TurnTimer>()
+                    .run_unless_resource_exists::<PatternPreview>()
+                    .run_unless_resource_exists::<AdvanceDelay>(),
+            )
+            .add_system(update_turn_timer_bar);
+    }
+}
+
+const TURN_TIMER_BAR_HEIGHT: f32 = 1.0;
+const TURN_TIMER_FULL: Color = Color::rgb(0.2, 0.8, 0.2);
+const TURN_TIMER_EMPTY: Color = Color::rgb(0.9, 0.15, 0.1);
+
+/// Time left for the next press
+#[derive(Resource)]
+struct TurnTimer(Timer);
+
+/// Marker component for the bar along the top of the screen
+#[derive(Component)]
+struct TurnTimerBar;
+
+/// Spawns the bar, hidden until there's a timer to show
+fn setup_turn_timer_bar(mut commands: Commands) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(0.0),
+                    left: Val::Px(0.0),
+                    ..Default::default()
+                },
+                size: Size::new(Val::Percent(100.0), Val::Percent(TURN_TIMER_BAR_HEIGHT)),
+                ..Default::default()
+            },
+            background_color: TURN_TIMER_FULL.into(),
+            visibility: Visibility::INVISIBLE,
+            ..Default::default()
+        })
+        .insert(TurnTimerBar);
+}
+
+/// Starts the timer for the first press, if it's turned on
+///
+/// Zen mode is meant to be relaxing, so it never has one
+fn start_turn_timer(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    game_mode: Res<GameMode>,
+    timings: Res<ButtonTimings>,
+) {
+    if *game_mode == GameMode::Zen {
+        return;
+    }
+    if let Some(seconds) = settings.turn_timer {
+        commands.insert_resource(TurnTimer(Timer::from_seconds(
+            timings.turn_time(seconds, &settings),
+            TimerMode::Once,
+        )));
+    }
+}
+
+/// Throws away the timer when the round ends
+fn end_turn_timer(mut commands: Commands) {
+    commands.remove_resource::<TurnTimer>();
+}
+
+/// Refills the timer on each correct press, and fails the round when it runs out
+///
+/// Presses made with little time left count as close calls for adaptive
+/// difficulty. Reads the events by hand so it can send the failure too
+fn tick_turn_timer(
+    mut commands: Commands,
+    mut turn_timer: ResMut<TurnTimer>,
+    mut reader: Local<ManualEventReader<SimonEvent>>,
+    mut events: ResMut<Events<SimonEvent>>,
+    mut performance: ResMut<Performance>,
+    time: Res<Time>,
+) {
+    let pressed = reader
+        .iter(&events)
+        .any(|event| matches!(event, SimonEvent::Next | SimonEvent::Success));
+    if pressed {
+        if turn_timer.0.percent_left() < CLOSE_CALL_TIME_LEFT {
+            performance.close_call = true;
+        }
+        turn_timer.0.reset();
+    }
+    if turn_timer.0.tick(time.delta()).just_finished() {
+        events.send(SimonEvent::Failure);
+        commands.remove_resource::<TurnTimer>();
+    }
+}
+
+/// Shrinks the bar with the time left, fading it from green to red
+fn update_turn_timer_bar(
+    turn_timer: Option<Res<TurnTimer>>,
+    mut bars: Query<(&mut Style, &mut BackgroundColor, &mut Visibility), With<TurnTimerBar>>,
+) {
+    for (mut style, mut color, mut visibility) in bars.iter_mut() {
+        let Some(turn_timer) = &turn_timer else {
+            if visibility.is_visible {
+                visibility.is_visible = false;
+            }
+            continue;
+        };
+        let left = turn_timer.0.percent_left();
+        visibility.is_visible = true;
+        style.size.width = Val::Percent(left * 100.0);
+        let [full_r, full_g, full_b, _] = TURN_TIMER_FULL.as_rgba_f32();
+        let [empty_r, empty_g, empty_b, _] = TURN_TIMER_EMPTY.as_rgba_f32();
+        color.0 = Color::rgb(
+            empty_r + (full_r - empty_r) * left,
+            empty_g + (full_g - empty_g) * left,
+            empty_b + (full_b - empty_b) * left,
+        );
+    }
+}
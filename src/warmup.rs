@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+
+use crate::{
+    menu::{MenuAction, MenuActions},
+    settings::Settings,
+    GameMode,
+};
+
+/// An unscored round at the start of each run to get into the rhythm
+pub struct WarmupPlugin;
+
+impl Plugin for WarmupPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WarmupDone>()
+            // The round starts the frame after the menu starts the run, so the
+            // flag has to be set by then
+            .add_system(start_warmup.after(MenuActions));
+    }
+}
+
+/// How many buttons the warm-up pattern has
+pub const WARMUP_LENGTH: usize = 3;
+
+/// Whether this run's warm-up is over, or there wasn't one
+#[derive(Resource)]
+pub struct WarmupDone(pub bool);
+
+impl Default for WarmupDone {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+/// Gives each new run a warm-up if it's turned on
+///
+/// Retrying goes straight back to the failed pattern, and continuing keeps
+/// the run going, so neither gets one. Classic hardware games never do, like
+/// the original
+fn start_warmup(
+    mut warmup_done: ResMut<WarmupDone>,
+    mut menu_events: EventReader<MenuAction>,
+    settings: Res<Settings>,
+    game_mode: Res<GameMode>,
+) {
+    for event in menu_events.iter() {
+        match event {
+            MenuAction::Play | MenuAction::Practice | MenuAction::Zen => {
+                warmup_done.0 = !settings.warm_up
+            }
+            // Playing again keeps the game mode
+            MenuAction::PlayAgain => {
+                warmup_done.0 = !settings.warm_up || *game_mode == GameMode::Hardware
+            }
+            MenuAction::Hardware | MenuAction::Retry => warmup_done.0 = true,
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use std::f32::consts::TAU;
+
+use bevy::{
+    audio::{play_queued_audio_system, AudioOutput, AudioSink, Decodable, Source},
+    prelude::*,
+    reflect::TypeUuid,
+};
+use iyes_loopless::prelude::*;
+
+use crate::{settings::Settings, ButtonEvent, GameMode, Lamp, SimonState};
+
+/// A calm mode with soft lights, ambient music and no way to lose
+pub struct ZenPlugin;
+
+impl Plugin for ZenPlugin {
+    fn build(&self, app: &mut App) {
+        // The pad is generated rather than loaded, so it gets its own audio output
+        app.init_non_send_resource::<AudioOutput<AmbientPad>>()
+            .add_asset::<AmbientPad>()
+            .init_resource::<Audio<AmbientPad>>()
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                play_queued_audio_system::<AmbientPad>,
+            )
+            .add_startup_system(setup_ambient_pad)
+            .add_system(play_ambient_pad)
+            .add_system(duck_ambient_pad.after(play_ambient_pad))
+            .add_system(cycle_lamp_color);
+    }
+}
+
+/// How fast the lamp's hue turns, in degrees per second
+const LAMP_HUE_SPEED: f32 = 12.0;
+const AMBIENT_VOLUME: f32 = 0.4;
+const AMBIENT_SAMPLE_RATE: u32 = 44_100;
+/// An A major chord, slowly swelling in and out
+const AMBIENT_VOICES: [(f32, f32); 4] =
+    [(110.0, 0.07), (220.0, 0.11), (277.18, 0.13), (329.63, 0.17)];
+
+/// A soft, endless chord
+#[derive(TypeUuid)]
+#[uuid = "5b0cf6a4-8c1e-4a9b-9a53-6f2d3e1c7b10"]
+struct AmbientPad;
+
+/// Generates the `AmbientPad`
+///
+/// Each voice keeps its own tone and swell phases, wrapped to [0, 1), so the
+/// pad stays clean however long zen mode is left running
+struct AmbientPadDecoder {
+    phases: [(f32, f32); AMBIENT_VOICES.len()],
+}
+
+impl Iterator for AmbientPadDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let step = 1.0 / AMBIENT_SAMPLE_RATE as f32;
+        let value: f32 = AMBIENT_VOICES
+            .iter()
+            .zip(self.phases.iter_mut())
+            .map(|((frequency, swell), (tone, swell_phase))| {
+                let volume = 0.5 + 0.5 * (*swell_phase * TAU).sin();
+                let sample = (*tone * TAU).sin() * volume;
+                *tone = (*tone + frequency * step).fract();
+                *swell_phase = (*swell_phase + swell * step).fract();
+                sample
+            })
+            .sum();
+        Some(value * 0.1)
+    }
+}
+
+impl Source for AmbientPadDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        AMBIENT_SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<std::time::Duration> {
+        None
+    }
+}
+
+impl Decodable for AmbientPad {
+    type Decoder = AmbientPadDecoder;
+    type DecoderItem = f32;
+
+    fn decoder(&self) -> Self::Decoder {
+        AmbientPadDecoder {
+            phases: [(0.0, 0.0); AMBIENT_VOICES.len()],
+        }
+    }
+}
+
+/// The ambient pad and its sink while it's playing
+#[derive(Resource)]
+struct AmbientMusic {
+    pad: Handle<AmbientPad>,
+    sink: Option<Handle<AudioSink>>,
+}
+
+/// Adds the ambient pad
+fn setup_ambient_pad(mut commands: Commands, mut pads: ResMut<Assets<AmbientPad>>) {
+    commands.insert_resource(AmbientMusic {
+        pad: pads.add(AmbientPad),
+        sink: None,
+    });
+}
+
+/// Whether a zen game is being played
+fn in_zen_game(game_mode: GameMode, state: SimonState) -> bool {
+    game_mode == GameMode::Zen && matches!(state, SimonState::MonkeySee | SimonState::MonkeyDo)
+}
+
+/// Plays the ambient pad during zen games
+fn play_ambient_pad(
+    mut music: ResMut<AmbientMusic>,
+    audio: Res<Audio<AmbientPad>>,
+    sinks: Res<Assets<AudioSink>>,
+    game_mode: Res<GameMode>,
+    state: Res<CurrentState<SimonState>>,
+) {
+    let playing = in_zen_game(*game_mode, state.0);
+    if playing && music.sink.is_none() {
+        let sink = audio.play_with_settings(
+            music.pad.clone(),
+            PlaybackSettings::LOOP.with_volume(AMBIENT_VOLUME),
+        );
+        music.sink = Some(sinks.get_handle(sink));
+    } else if !playing {
+        if let Some(sink) = music.sink.take() {
+            if let Some(sink) = sinks.get(&sink) {
+                sink.stop();
+            }
+        }
+    }
+}
+
+/// Lowers the ambient pad whenever a button sounds, bringing it back up
+/// over the duck release time so the buttons can always be heard
+fn duck_ambient_pad(
+    music: Res<AmbientMusic>,
+    sinks: Res<Assets<AudioSink>>,
+    mut button_event_reader: EventReader<ButtonEvent>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+    mut duck: Local<f32>,
+) {
+    // Ducks all the way straight away, then eases off
+    if button_event_reader.iter().next().is_some() {
+        *duck = 1.0;
+    } else if settings.duck_release > 0.0 {
+        *duck = (*duck - time.delta_seconds() / settings.duck_release).max(0.0);
+    } else {
+        *duck = 0.0;
+    }
+    button_event_reader.clear();
+
+    let Some(sink) = music.sink.as_ref().and_then(|sink| sinks.get(sink)) else {
+        return;
+    };
+    let amount = settings.music_ducking.unwrap_or(0.0);
+    let volume = AMBIENT_VOLUME * (1.0 - amount * *duck);
+    if sink.volume() != volume {
+        sink.set_volume(volume);
+    }
+}
+
+/// Slowly turns the lamp through soft colors during zen games
+fn cycle_lamp_color(
+    mut lamps: Query<&mut SpotLight, With<Lamp>>,
+    game_mode: Res<GameMode>,
+    state: Res<CurrentState<SimonState>>,
+    time: Res<Time>,
+    mut hue: Local<f32>,
+) {
+    let color = if in_zen_game(*game_mode, state.0) {
+        *hue = (*hue + LAMP_HUE_SPEED * time.delta_seconds()) % 360.0;
+        Color::hsl(*hue, 0.5, 0.85)
+    } else {
+        *hue = 0.0;
+        Color::WHITE
+    };
+    for mut spot_light in lamps.iter_mut() {
+        if spot_light.color != color {
+            spot_light.color = color;
+        }
+    }
+}